@@ -0,0 +1,143 @@
+use std::{env::args_os, fmt::Display};
+
+use crate::beautify::Beautify;
+
+/// The arguments given to run.
+#[derive(Default)]
+pub struct Args {
+    /// The first argument which isn't a flag of run, like a script name, an alias, or `--init`.
+    param: Option<String>,
+    /// Whether the confirmations are given beforehand or not.
+    yes: bool,
+    /// Whether to wait for the locks held by others or not.
+    wait: bool,
+    /// Whether to display the resources used by the scripts or not.
+    stats: bool,
+    /// The format and the target of the events, like `json` or `json:events.ndjson`.
+    events: Option<String>,
+    /// The file which the trace is saved to.
+    trace: Option<String>,
+    /// The formats and the files of the reports, like `junit=report.xml`.
+    reports: Vec<String>,
+    /// The arguments after the parameter.
+    rest: Vec<String>,
+}
+
+/// The error type for the arguments.
+pub enum ArgsError {
+    NoValue(&'static str, &'static str),
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoValue(flag, value) => write!(
+                f,
+                "{} {} {}",
+                flag.yellow(),
+                "needs a value, like".red(),
+                format!("{} {}", flag, value).yellow()
+            ),
+        }
+    }
+}
+
+impl Args {
+    /// Collects the arguments given to the process.
+    ///
+    /// The flags of run are only looked for before the first parameter.
+    pub fn parse() -> Result<Args, ArgsError> {
+        let mut args = Args::default();
+
+        // Arguments that aren't valid Unicode are treated as empty.
+        let mut iter = args_os()
+            .skip(1)
+            .map(|arg| arg.into_string().unwrap_or_default());
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-y" | "--yes" => args.yes = true,
+                "-w" | "--wait" => args.wait = true,
+                "--stats" => args.stats = true,
+                events if events.starts_with("--events=") => {
+                    args.events = Some(events["--events=".len()..].to_string())
+                }
+                "--trace" => args.trace = Some(value_of("--trace", "trace.json", iter.next())?),
+                "--report" => {
+                    args.reports
+                        .push(value_of("--report", "junit=report.xml", iter.next())?)
+                }
+                report if report.starts_with("--report=") => {
+                    let value = report["--report=".len()..].to_string();
+
+                    args.reports
+                        .push(value_of("--report", "junit=report.xml", Some(value))?)
+                }
+                trace if trace.starts_with("--trace=") => {
+                    let value = trace["--trace=".len()..].to_string();
+
+                    args.trace = Some(value_of("--trace", "trace.json", Some(value))?)
+                }
+                _ => {
+                    args.param = Some(arg);
+                    break;
+                }
+            }
+        }
+
+        args.rest = iter.collect();
+
+        Ok(args)
+    }
+
+    /// Returns `self.param`.
+    pub fn param(&self) -> Option<&str> {
+        self.param.as_deref()
+    }
+
+    /// Returns `self.yes`.
+    pub fn yes(&self) -> bool {
+        self.yes
+    }
+
+    /// Returns `self.wait`.
+    pub fn wait(&self) -> bool {
+        self.wait
+    }
+
+    /// Returns `self.stats`.
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+
+    /// Returns `self.events`.
+    pub fn events(&self) -> Option<&str> {
+        self.events.as_deref()
+    }
+
+    /// Returns `self.trace`.
+    pub fn trace(&self) -> Option<&str> {
+        self.trace.as_deref()
+    }
+
+    /// Returns `self.reports`.
+    pub fn reports(&self) -> &[String] {
+        &self.reports
+    }
+
+    /// Returns `self.rest`.
+    pub fn rest(&self) -> &[String] {
+        &self.rest
+    }
+}
+
+/// Returns the value of the flag, or an error with an example of the value if it's missing or empty.
+fn value_of(
+    flag: &'static str,
+    example: &'static str,
+    value: Option<String>,
+) -> Result<String, ArgsError> {
+    value
+        .filter(|value| !value.is_empty())
+        .ok_or(ArgsError::NoValue(flag, example))
+}
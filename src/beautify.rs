@@ -42,25 +42,25 @@ where
     T: 'a + Display,
 {
     /// Makes the text green.
-    fn green(&self) -> Beautiful<T> {
+    fn green(&self) -> Beautiful<'_, T> {
         Beautiful {
-            content: &self,
+            content: self,
             color: colors::GREEN,
         }
     }
 
     /// Makes the text red.
-    fn red(&self) -> Beautiful<T> {
+    fn red(&self) -> Beautiful<'_, T> {
         Beautiful {
-            content: &self,
+            content: self,
             color: colors::RED,
         }
     }
 
     /// Makes the text yellow.
-    fn yellow(&self) -> Beautiful<T> {
+    fn yellow(&self) -> Beautiful<'_, T> {
         Beautiful {
-            content: &self,
+            content: self,
             color: colors::YELLOW,
         }
     }
@@ -0,0 +1,126 @@
+use std::{env, sync::OnceLock};
+
+use crate::state;
+
+/// The CI environments, which are detected by their environment variables.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Ci {
+    GithubActions,
+    Gitlab,
+    AzurePipelines,
+    Buildkite,
+    TeamCity,
+    /// A CI environment which only sets `CI`, whose output has no special markers.
+    Other,
+}
+
+/// Returns the CI environment run is executed in, if any.
+pub fn detect() -> Option<Ci> {
+    static CI: OnceLock<Option<Ci>> = OnceLock::new();
+
+    *CI.get_or_init(|| {
+        let is_set = |name: &str| env::var_os(name).is_some_and(|value| !value.is_empty());
+
+        if is_set("GITHUB_ACTIONS") {
+            Some(Ci::GithubActions)
+        } else if is_set("GITLAB_CI") {
+            Some(Ci::Gitlab)
+        } else if is_set("TF_BUILD") {
+            Some(Ci::AzurePipelines)
+        } else if is_set("BUILDKITE") {
+            Some(Ci::Buildkite)
+        } else if is_set("TEAMCITY_VERSION") {
+            Some(Ci::TeamCity)
+        } else if env::var("CI").is_ok_and(|ci| ci != "false" && ci != "0" && !ci.is_empty()) {
+            Some(Ci::Other)
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns `true` if run is executed in a CI environment.
+pub fn is_ci() -> bool {
+    detect().is_some()
+}
+
+/// Prints the marker starting a collapsible group of the output, if the CI environment supports it.
+pub fn start_group(id: &str, title: &str) {
+    match detect() {
+        Some(Ci::GithubActions) => println!("::group::{}", title),
+        Some(Ci::Gitlab) => println!(
+            "\x1B[0Ksection_start:{}:{}[collapsed=true]\r\x1B[0K{}",
+            state::now(),
+            section_id(id),
+            title
+        ),
+        Some(Ci::AzurePipelines) => println!("##[group]{}", title),
+        Some(Ci::Buildkite) => println!("--- {}", title),
+        Some(Ci::TeamCity) => println!("##teamcity[blockOpened name='{}']", escape_teamcity(title)),
+        Some(Ci::Other) | None => (),
+    }
+}
+
+/// Prints the marker ending a collapsible group of the output, if the CI environment supports it.
+pub fn end_group(id: &str, title: &str) {
+    match detect() {
+        Some(Ci::GithubActions) => println!("::endgroup::"),
+        Some(Ci::Gitlab) => println!(
+            "\x1B[0Ksection_end:{}:{}\r\x1B[0K",
+            state::now(),
+            section_id(id)
+        ),
+        Some(Ci::AzurePipelines) => println!("##[endgroup]"),
+        Some(Ci::TeamCity) => println!("##teamcity[blockClosed name='{}']", escape_teamcity(title)),
+        Some(Ci::Buildkite) | Some(Ci::Other) | None => (),
+    }
+}
+
+/// Prints an error annotation, which is displayed on the summary of the build, if the CI environment supports it.
+pub fn error(message: &str) {
+    match detect() {
+        Some(Ci::GithubActions) => println!("::error title=run::{}", escape_github(message)),
+        Some(Ci::AzurePipelines) => println!("##vso[task.logissue type=error]{}", message),
+        // Expand the group of the failed script, which is collapsed by default.
+        Some(Ci::Buildkite) => println!("^^^ +++"),
+        Some(Ci::TeamCity) => println!(
+            "##teamcity[buildProblem description='{}']",
+            escape_teamcity(message)
+        ),
+        Some(Ci::Gitlab) | Some(Ci::Other) | None => (),
+    }
+}
+
+/// Converts the text to an id of a GitLab section, which can only have letters, digits, `_`, `.`, and `-`.
+fn section_id(text: &str) -> String {
+    text.chars()
+        .map(|ch| match ch {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => ch,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Escapes the special characters of the workflow commands of GitHub Actions.
+fn escape_github(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes the special characters of the service messages of TeamCity.
+fn escape_teamcity(text: &str) -> String {
+    text.replace('|', "||")
+        .replace('\'', "|'")
+        .replace('\n', "|n")
+        .replace('\r', "|r")
+        .replace('[', "|[")
+        .replace(']', "|]")
+}
+
+#[test]
+fn test() {
+    assert_eq!(escape_github("50% done\nnext"), "50%25 done%0Anext");
+    assert_eq!(escape_teamcity("it's [x]"), "it|'s |[x|]");
+    assert_eq!(section_id("build:web app"), "build_web_app");
+}
@@ -0,0 +1,245 @@
+use std::{
+    io::{stderr, IsTerminal, Write},
+    path::PathBuf,
+    process::Stdio,
+    time::{Duration, Instant},
+};
+
+use crate::{args::Args, beautify::Beautify, ci, prompt, script};
+
+use super::{db::Database, errors::DatabaseError};
+
+/// The number of runs measured by `run bench`, unless `-n` is given.
+const DEFAULT_RUNS: usize = 10;
+
+/// The number of runs before measuring, unless `--warmup` is given.
+const DEFAULT_WARMUP: usize = 1;
+
+/// The statistics of the durations of a script.
+struct Summary {
+    mean: f64,
+    median: f64,
+    stddev: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Summary {
+    /// Calculates the statistics of the durations, in seconds.
+    fn of(durations: &[f64]) -> Summary {
+        let mut sorted = durations.to_vec();
+
+        sorted.sort_by(f64::total_cmp);
+
+        let len = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / len as f64;
+
+        let median = match len % 2 {
+            0 => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+            _ => sorted[len / 2],
+        };
+
+        // The sample standard deviation, which is zero for a single run.
+        let stddev = match len {
+            1 => 0.0,
+            _ => {
+                let variance =
+                    sorted.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (len - 1) as f64;
+                variance.sqrt()
+            }
+        };
+
+        Summary {
+            mean,
+            median,
+            stddev,
+            min: sorted[0],
+            max: sorted[len - 1],
+        }
+    }
+}
+
+impl<'a> Database<'a> {
+    /// Executes the scripts repeatedly, then prints the statistics of their durations.
+    ///
+    /// If two scripts are given, also prints how many times faster one is than the other.
+    /// Only the commands of the scripts are executed, without their dependencies, and their output is discarded.
+    pub fn bench(&'a self, args: &'a [String], run_args: &Args) -> Result<(), DatabaseError<'a>> {
+        let mut aliases_or_names = vec![];
+        let mut runs = DEFAULT_RUNS;
+        let mut warmup = DEFAULT_WARMUP;
+
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-n" | "--runs" => runs = parse_count(iter.next(), 1)?,
+                "--warmup" => warmup = parse_count(iter.next(), 0)?,
+                _ => aliases_or_names.push(arg.as_str()),
+            }
+        }
+
+        if aliases_or_names.is_empty() || aliases_or_names.len() > 2 {
+            return Err(DatabaseError::NoBenchName);
+        }
+
+        let mut summaries = vec![];
+
+        let extra_paths = self.extra_paths();
+
+        for alias_or_name in aliases_or_names {
+            let (name, script) = self.get(alias_or_name)?;
+
+            // There is nothing to measure, as the dependencies aren't executed.
+            if script.command().is_empty() {
+                return Err(DatabaseError::NoBenchCommand(name));
+            }
+
+            let _lock = self.confirm_and_lock(name, script, run_args, prompt::is_interactive())?;
+
+            println!(
+                "{} {} {}",
+                "bench".green(),
+                name.yellow(),
+                format!("({} runs, {} warmup)", runs, warmup).yellow()
+            );
+
+            let mut durations = vec![];
+
+            for i in 0..warmup + runs {
+                show_progress(i, warmup, runs);
+
+                let start_time = Instant::now();
+
+                let exit_code = execute(script.command(), &extra_paths);
+
+                let duration = start_time.elapsed();
+
+                // The durations of failed runs mean nothing.
+                if exit_code != 0 {
+                    show_progress(warmup + runs, warmup, runs);
+                    return Err(DatabaseError::BenchFailed(name, exit_code));
+                }
+
+                if i >= warmup {
+                    durations.push(duration.as_secs_f64());
+                }
+            }
+
+            show_progress(warmup + runs, warmup, runs);
+
+            let summary = Summary::of(&durations);
+
+            print_summary(&summary);
+
+            summaries.push((name, summary));
+        }
+
+        // Compare the scripts, if there are two of them.
+        if let [(first_name, first), (second_name, second)] = summaries.as_slice() {
+            let (fast_name, fast, slow_name, slow) = match first.mean <= second.mean {
+                true => (first_name, first, second_name, second),
+                false => (second_name, second, first_name, first),
+            };
+
+            let ratio = slow.mean / fast.mean;
+
+            // The error of the ratio, propagated from the standard deviations.
+            let error = ratio
+                * ((slow.stddev / slow.mean).powi(2) + (fast.stddev / fast.mean).powi(2)).sqrt();
+
+            println!(
+                "{} {} {} {}",
+                fast_name.yellow(),
+                "is".green(),
+                format!("{:.2} ± {:.2}", ratio, error).yellow(),
+                format!("times faster than {}", slow_name).green()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Executes the command without its output, then returns its exit code.
+fn execute(command: &str, extra_paths: &[PathBuf]) -> i32 {
+    script::shell(command, extra_paths)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.code().unwrap_or(1))
+        .unwrap_or(1)
+}
+
+/// Parses a count given to a flag, which must be at least `min`.
+fn parse_count(value: Option<&String>, min: usize) -> Result<usize, DatabaseError<'_>> {
+    let value = value.map(String::as_str).unwrap_or_default();
+
+    match value.parse() {
+        Ok(count) if count >= min => Ok(count),
+        _ => Err(DatabaseError::BadCount(value)),
+    }
+}
+
+/// Shows which run is being executed, if stderr is a terminal outside of CI, and clears it after the last run.
+fn show_progress(i: usize, warmup: usize, runs: usize) {
+    if !stderr().is_terminal() || ci::is_ci() {
+        return;
+    }
+
+    let progress = match i {
+        i if i < warmup => format!("warmup {}/{}", i + 1, warmup),
+        i if i < warmup + runs => format!("run {}/{}", i - warmup + 1, runs),
+        _ => String::new(),
+    };
+
+    // We don't need to be warned, cuz the progress is only a hint.
+    #[allow(unused_must_use)]
+    {
+        let mut stderr = stderr().lock();
+        write!(stderr, "\r\x1B[2K{}", progress);
+        stderr.flush();
+    }
+}
+
+/// Prints the statistics of a script.
+fn print_summary(summary: &Summary) {
+    let seconds = |secs: f64| format!("{:.2?}", Duration::from_secs_f64(secs));
+
+    let rows = [
+        (
+            "mean",
+            format!("{} ± {}", seconds(summary.mean), seconds(summary.stddev)),
+        ),
+        ("median", seconds(summary.median)),
+        ("min", seconds(summary.min)),
+        ("max", seconds(summary.max)),
+    ];
+
+    for (label, value) in rows {
+        println!(
+            "    {}  {}",
+            format!("{:<6}", label).green(),
+            value.yellow()
+        );
+    }
+
+    println!();
+}
+
+#[test]
+fn test() {
+    let summary = Summary::of(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+
+    assert_eq!(summary.mean, 5.0);
+    assert_eq!(summary.median, 4.5);
+    assert_eq!(summary.min, 2.0);
+    assert_eq!(summary.max, 9.0);
+    assert!((summary.stddev - 2.138).abs() < 0.001);
+
+    let summary = Summary::of(&[1.5]);
+
+    assert_eq!(summary.median, 1.5);
+    assert_eq!(summary.stddev, 0.0);
+}
@@ -1,32 +1,48 @@
 use std::{
+    borrow::Cow,
     collections::{BTreeMap, HashMap},
-    time::Instant,
+    path::PathBuf,
+    sync::OnceLock,
 };
 
 use crate::{
     beautify::Beautify,
     file::{self, FileError},
+    history,
     script::Script,
+    settings::Settings,
+    toolchain,
 };
 
-use super::errors::{DatabaseError, FormatError};
+use super::{
+    errors::{DatabaseError, FormatError},
+    parse::{options_of, rename_runs, saved_command, settings_of, SETTINGS},
+};
 
 /// The type representing a B-Tree map for scripts.
-pub type ScriptMap<'a> = BTreeMap<&'a str, Script<'a>>;
+pub type ScriptMap<'a> = BTreeMap<Cow<'a, str>, Script<'a>>;
 
 /// The type for an alias name.
 pub enum AliasName<'a> {
-    Name(&'a str),
+    Name(Cow<'a, str>),
     Multi,
 }
 
 /// The type representing a hash map for aliasses.
 pub type AliasMap<'a> = HashMap<char, AliasName<'a>>;
 
-/// A database stores scripts, their names, and aliasses.
+/// The prefix of the scripts read from a source, and the name of the source file, like `npm` and `package.json`.
+pub type Source = (&'static str, &'static str);
+
+/// A database stores scripts, their names, aliasses, and the settings.
 pub struct Database<'a> {
     script_map: ScriptMap<'a>,
     alias_map: AliasMap<'a>,
+    settings: Settings,
+    /// The sources of the scripts, if they are read from several manifests without run.yaml.
+    sources: Vec<Source>,
+    /// The directories which may have the executables used by the scripts, found once per run.
+    candidate_paths: OnceLock<Vec<PathBuf>>,
 }
 
 impl<'a> Database<'a> {
@@ -35,32 +51,152 @@ impl<'a> Database<'a> {
         Database {
             script_map: ScriptMap::new(),
             alias_map: AliasMap::new(),
+            settings: Settings::default(),
+            sources: vec![],
+            candidate_paths: OnceLock::new(),
         }
     }
 
+    /// Returns `self.settings`.
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Returns a mutable reference to `self.settings`.
+    pub(super) fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+
     /// Prints all the available scripts' names and comments.
+    ///
+    /// If the scripts are read from several sources, they are grouped by their sources.
     pub fn print(&self) {
         let mut content = "Run:\n".green().to_string();
 
-        for (name, script) in &self.script_map {
-            content += &format!("    {}  {}\n", name.yellow(), script.comment().green())
+        let entries = history::read();
+
+        let line = |name: &str, script: &Script| {
+            // Show the typical duration of the script, if it has run enough.
+            let typical = match history::typical(&entries, name) {
+                Some(typical) => format!("  {}", format!("usually {:.2?}", typical).yellow()),
+                None => String::new(),
+            };
+
+            format!(
+                "    {}  {}{}\n",
+                name.yellow(),
+                script.comment().green(),
+                typical
+            )
+        };
+
+        if self.sources.is_empty() {
+            for (name, script) in &self.script_map {
+                content += &line(name, script);
+            }
+        }
+
+        for (index, (prefix, file_name)) in self.sources.iter().enumerate() {
+            if index > 0 {
+                content += "\n";
+            }
+
+            content += &format!("  {}\n", file_name.green());
+
+            for (name, script) in &self.script_map {
+                if self.split_source(name).0 == Some(prefix) {
+                    content += &line(name, script);
+                }
+            }
         }
 
         println!("{}", content);
     }
 
+    /// Splits the name into the prefix of its source and the name in the source, like `npm` and `build` for `npm:build`.
+    pub(super) fn split_source<'n>(&self, name: &'n str) -> (Option<&'static str>, &'n str) {
+        for (prefix, _) in &self.sources {
+            if let Some(rest) = name
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix(':'))
+            {
+                return (Some(prefix), rest);
+            }
+        }
+
+        (None, name)
+    }
+
+    /// Adds the scripts of the database read from a source, with the prefix of the source, like `npm:build`.
+    ///
+    /// The dependencies of the scripts are also prefixed, but the aliases are made from the names without the prefix.
+    pub(super) fn add_source(
+        &mut self,
+        prefix: &'static str,
+        file_name: &'static str,
+        db: Database<'a>,
+    ) -> Result<(), FormatError> {
+        if db.settings.npm_compat() {
+            self.settings.set_npm_compat(true);
+        }
+
+        for (name, mut script) in db.script_map {
+            rename_references(&mut script, |name| format!("{}:{}", prefix, name));
+
+            let alias = name.chars().next();
+
+            self.add_with_alias(format!("{}:{}", prefix, name), alias, script)?;
+        }
+
+        self.sources.push((prefix, file_name));
+
+        Ok(())
+    }
+
     /// Extracts the entire database into a `String`, to be saved later.
     pub(super) fn extract(&self) -> String {
         let mut extraction = String::new();
 
+        let settings = settings_of(&self.settings);
+
+        // The settings are extracted into a block at the beginning, if there are any.
+        if !settings.is_empty() {
+            extraction = format!("# The settings of run.\n{}:\n", SETTINGS);
+
+            for (key, value) in settings {
+                extraction = format!("{}  {}: {}\n", extraction, key, value);
+            }
+
+            extraction += "\n";
+        }
+
         for (name, script) in &self.script_map {
-            extraction = format!(
-                "{}# {}\n{}: {}\n\n",
-                extraction,
-                script.comment(),
-                name,
-                script.command()
-            );
+            let options = options_of(script);
+
+            // Scripts without options are extracted into a single line.
+            if options.is_empty() {
+                extraction = format!(
+                    "{}# {}\n{}: {}\n\n",
+                    extraction,
+                    script.comment(),
+                    name,
+                    script.command()
+                );
+            }
+            // Scripts with options are extracted into a block, having its command and options indented.
+            else {
+                extraction = format!("{}# {}\n{}:\n", extraction, script.comment(), name);
+
+                if !script.command().is_empty() {
+                    extraction = format!("{}  command: {}\n", extraction, saved_command(script));
+                }
+
+                for (key, value) in options {
+                    extraction = format!("{}  {}: {}\n", extraction, key, value);
+                }
+
+                extraction += "\n";
+            }
         }
 
         extraction
@@ -68,8 +204,11 @@ impl<'a> Database<'a> {
 
     #[cfg(test)]
     /// Returns a vector of tuples containing scripts and their names.
-    pub(super) fn scripts_and_names(&self) -> Vec<(&&'a str, &'a Script)> {
-        self.script_map.iter().collect()
+    pub(super) fn scripts_and_names(&self) -> Vec<(&str, &Script<'a>)> {
+        self.script_map
+            .iter()
+            .map(|(name, script)| (name.as_ref(), script))
+            .collect()
     }
 
     /// Saves the database to run.yaml file in the current directory.
@@ -77,6 +216,11 @@ impl<'a> Database<'a> {
         file::create("run.yaml", &self.extract())
     }
 
+    /// Saves the database to run.yaml file in the current directory, overwriting the existing one.
+    pub fn overwrite(&self) -> Result<(), FileError> {
+        file::write("run.yaml", &self.extract())
+    }
+
     /// Saves the database to run.yaml file in the current directory.
     pub fn save_if_bad(&self, run_yaml: &'a str) {
         let extraction = self.extract();
@@ -90,63 +234,28 @@ impl<'a> Database<'a> {
         };
     }
 
-    /// Executes the associated script, and then returns it's exit code.
-    pub fn run(&self, alias_or_name: &'a str) -> Result<i32, DatabaseError> {
-        let (name, script) = self.get(alias_or_name)?;
-
-        println!("{} {}\n", "run".green(), name.yellow());
-
-        let start_time = Instant::now();
-
-        let exit_code = if file::exists("package.json") {
-            script.execute(Some("node_modules/.bin"))
-        } else {
-            script.execute(None)
-        };
+    /// Consumes the database, then returns its names and scripts.
+    pub(super) fn into_scripts(self) -> impl Iterator<Item = (Cow<'a, str>, Script<'a>)> {
+        self.script_map.into_iter()
+    }
 
-        let end_time = start_time.elapsed();
-
-        match exit_code {
-            0 => println!(
-                "\n{} {}",
-                "in".green(),
-                format!("{:.2?}", end_time).yellow()
-            ),
-
-            2 => println!("\n{}", "permission denied".red()),
-
-            126 => println!(
-                "\n{} {}",
-                script
-                    .command()
-                    .split_once(" ")
-                    .unwrap_or((script.command(), ""))
-                    .0
-                    .yellow()
-                    .yellow(),
-                "can't be executed".red()
-            ),
-
-            127 => println!(
-                "\n{} {}",
-                script
-                    .command()
-                    .split_once(" ")
-                    .unwrap_or((script.command(), ""))
-                    .0
-                    .yellow()
-                    .yellow(),
-                "is not found".red()
-            ),
-
-            _ => println!("\n{} {}", "error code".red(), exit_code.green()),
-        }
+    /// Returns `true` if there is a script with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.script_map.contains_key(name)
+    }
 
-        Ok(exit_code)
+    /// Returns an iterator over the names and scripts.
+    pub(super) fn scripts(&'a self) -> impl Iterator<Item = (&'a str, &'a Script<'a>)> {
+        self.script_map
+            .iter()
+            .map(|(name, script)| (name.as_ref(), script))
     }
 
     /// Returns a tuple of associated `Script` and its name.
-    fn get(&self, alias_or_name: &'a str) -> Result<(&'a str, &'a Script), DatabaseError<'a>> {
+    pub(super) fn get(
+        &'a self,
+        alias_or_name: &'a str,
+    ) -> Result<(&'a str, &'a Script<'a>), DatabaseError<'a>> {
         let chars: Vec<char> = alias_or_name.chars().collect();
 
         if chars.len() == 1 {
@@ -155,7 +264,7 @@ impl<'a> Database<'a> {
             match self.alias_map.get(&alias) {
                 Some(alias_name) => match alias_name {
                     AliasName::Name(name) => match self.script_map.get(name) {
-                        Some(script) => Ok((name, script)),
+                        Some(script) => Ok((name.as_ref(), script)),
                         None => Err(DatabaseError::NoName(name)),
                     },
 
@@ -164,29 +273,72 @@ impl<'a> Database<'a> {
 
                 None => Err(DatabaseError::NoAlias(alias)),
             }
+        } else if let Some((name, script)) = self.script_map.get_key_value(alias_or_name) {
+            Ok((name.as_ref(), script))
         } else {
-            let name = alias_or_name;
-
-            match self.script_map.get(name) {
-                Some(script) => Ok((name, script)),
-                None => Err(DatabaseError::NoName(name)),
+            // The scripts read from several sources can also be run without their prefixes, if only one source has them.
+            let mut prefixed = self.sources.iter().filter_map(|(prefix, _)| {
+                self.script_map
+                    .get_key_value(format!("{}:{}", prefix, alias_or_name).as_str())
+            });
+
+            match (prefixed.next(), prefixed.next()) {
+                (Some((name, script)), None) => Ok((name.as_ref(), script)),
+                _ => Err(DatabaseError::NoName(alias_or_name)),
             }
         }
     }
 
+    /// Returns the directories of the executables used by the scripts, which are searched before `$PATH`.
+    ///
+    /// Only the existing ones are returned, as they may be created by a dependency, like `npm install`.
+    pub(super) fn extra_paths(&self) -> Vec<PathBuf> {
+        self.candidate_paths
+            .get_or_init(|| toolchain::candidate_paths(self.settings.path()))
+            .iter()
+            .filter(|path| path.is_dir())
+            .cloned()
+            .collect()
+    }
+
     /// Adds a new script and its name to `self.script_map`.
     ///
     /// Also creates an alias for the name, if possible.
-    pub(super) fn add(&mut self, name: &'a str, script: Script<'a>) -> Result<(), FormatError> {
-        let alias = match name.chars().nth(0) {
+    pub(super) fn add(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        script: Script<'a>,
+    ) -> Result<(), FormatError> {
+        let name = name.into();
+        let alias = name.chars().nth(0);
+
+        self.add_with_alias(name, alias, script)
+    }
+
+    /// Adds a new script and its name to `self.script_map`, with the character its alias is created from.
+    fn add_with_alias(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        alias: Option<char>,
+        script: Script<'a>,
+    ) -> Result<(), FormatError> {
+        let name = name.into();
+
+        let alias = match alias {
             Some(ch) => ch,
             None => return Err(FormatError::NoName),
         };
 
-        if script.command().is_empty() {
+        // A script without a command only runs its dependencies.
+        if script.command().is_empty() && script.depends().next().is_none() {
             return Err(FormatError::NoCommand);
         }
 
+        // Only a service has an output to be ready.
+        if script.ready_when().is_some() && !script.service() {
+            return Err(FormatError::NotService);
+        }
+
         if name.starts_with("-") {
             return Err(FormatError::MinusInStartOfName);
         }
@@ -196,7 +348,7 @@ impl<'a> Database<'a> {
         }
 
         // If the same key is used before, return `UsedName` error.
-        match self.script_map.insert(name, script) {
+        match self.script_map.insert(name.clone(), script) {
             Some(_) => Err(FormatError::UsedName),
             None => {
                 // Try to get the value of given key.
@@ -217,3 +369,16 @@ impl<'a> Database<'a> {
         }
     }
 }
+
+/// Renames the scripts the script references, which are its dependencies and the scripts its command runs with `RUN`.
+pub(super) fn rename_references(script: &mut Script, rename: impl Fn(&str) -> String) {
+    let depends: Vec<String> = script.depends().map(&rename).collect();
+
+    if !depends.is_empty() {
+        script.set_depends(depends.join(", "));
+    }
+
+    if let Some(command) = rename_runs(script.command(), &rename) {
+        script.set_command(command);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{beautify::Beautify, toolchain};
+
+use super::{db::Database, errors::DatabaseError};
+
+impl<'a> Database<'a> {
+    /// Displays the providers of the directories added to `PATH`, whether they are activated, and why.
+    pub fn doctor(&'a self) -> Result<(), DatabaseError<'a>> {
+        let providers = toolchain::providers(self.settings().path());
+
+        // Get the length of the longest name, to align the statuses.
+        let width = providers
+            .iter()
+            .map(|provider| provider.name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut content = "PATH providers:\n".green().to_string();
+
+        for provider in providers {
+            let name = format!("{:<width$}", provider.name, width = width);
+
+            let status = match provider.is_active() {
+                true => format!("{:<8}", "active").green().to_string(),
+                false => format!("{:<8}", "inactive").red().to_string(),
+            };
+
+            content += &format!(
+                "    {}  {}  {}\n",
+                name.yellow(),
+                status,
+                provider.reason.yellow()
+            );
+
+            // The directories are displayed in the order they are searched.
+            for path in provider.paths() {
+                content += &format!(
+                    "    {:width$}  {:<8}  {}\n",
+                    "",
+                    "",
+                    path.display().to_string().green(),
+                    width = width
+                );
+            }
+        }
+
+        println!("{}", content);
+
+        Ok(())
+    }
+}
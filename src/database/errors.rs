@@ -1,40 +1,124 @@
-use crate::beautify::Beautify;
+use std::{borrow::Cow, fmt::Display};
+
+use crate::{beautify::Beautify, lock::LockError, ready::ReadyError, service::ServiceError};
 
 /// The error type for database operations.
 pub enum DatabaseError<'a> {
     NoAlias(char),
     MultiAlias(char),
     NoName(&'a str),
+    NotConfirmed(&'a str),
+    Cancelled(&'a str),
+    Locked(LockError),
+    NotService(&'a str),
+    NoLogName,
+    NoLogs(&'a str),
+    Service(ServiceError),
+    NotReady(ReadyError),
+    CircularDependency(&'a str),
+    BadCount(&'a str),
+    NoHistory,
+    NoLongerExists(String),
+    NoBenchName,
+    NoBenchCommand(&'a str),
+    NoArgsCommand(&'a str),
+    BenchFailed(&'a str, i32),
+    BadInitFlag(&'a str),
+    NoTemplateName,
+    NoTemplate(&'a str, String),
 }
 
-impl<'a> ToString for DatabaseError<'a> {
-    fn to_string(&self) -> String {
+impl Display for DatabaseError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::MultiAlias(alias) => {
-                format!("there are multiple names starting with '{}'", alias)
+                write!(f, "there are multiple names starting with '{}'", alias)
+            }
+            Self::NoAlias(alias) => write!(f, "there is no name starting with '{}'", alias),
+            Self::NoName(name) => write!(f, "there isn't a script called '{}'", name),
+            Self::NotConfirmed(name) => write!(
+                f,
+                "'{}' needs a confirmation, use `--yes` to run it non-interactively",
+                name
+            ),
+            Self::Cancelled(name) => write!(f, "'{}' is cancelled", name),
+            Self::Locked(err) => err.fmt(f),
+            Self::NotService(name) => write!(
+                f,
+                "'{}' isn't a service, add `service: true` to make it one",
+                name
+            ),
+            Self::NoLogName => write!(f, "a single script name is needed"),
+            Self::NoLogs(name) => write!(
+                f,
+                "'{}' has no logs, add `log: true` to write its output to log files",
+                name
+            ),
+            Self::Service(err) => err.fmt(f),
+            Self::NotReady(err) => err.fmt(f),
+            Self::CircularDependency(name) => {
+                write!(f, "'{}' depends on itself through its dependencies", name)
+            }
+            Self::BadCount(count) => write!(f, "'{}' isn't a valid count", count),
+            Self::NoHistory => write!(f, "there is no script in the history"),
+            Self::NoBenchName => write!(f, "one or two script names are needed"),
+            Self::NoBenchCommand(name) => write!(
+                f,
+                "'{}' only runs its dependencies, which aren't benchmarked",
+                name
+            ),
+            Self::NoArgsCommand(name) => write!(
+                f,
+                "'{}' only runs its dependencies, which don't get the arguments",
+                name
+            ),
+            Self::BenchFailed(name, exit_code) => {
+                write!(f, "'{}' failed with error code {}", name, exit_code)
             }
-            Self::NoAlias(alias) => format!("there is no name starting with '{}'", alias),
-            Self::NoName(name) => format!("there isn't a script called '{}'", name),
+            Self::NoLongerExists(name) => {
+                write!(f, "there isn't a script called '{}' anymore", name)
+            }
+            Self::BadInitFlag(flag) => write!(
+                f,
+                "'{}' isn't a flag of `run -i`, use `--force` or `--merge`, and `--template <NAME>`",
+                flag
+            ),
+            Self::NoTemplateName => write!(f, "a template name is needed"),
+            Self::NoTemplate(name, names) => write!(
+                f,
+                "there isn't a template called '{}', the templates are {}",
+                name, names
+            ),
         }
     }
 }
 
 /// The error type for script format.
 pub enum FormatError {
+    BadCondition,
+    BadValue,
     MinusInStartOfName,
     NoName,
     NoCommand,
+    NotService,
     SpaceInName,
+    UnknownOption,
     UsedName,
 }
 
 impl FormatError {
-    pub fn into_parse_error(self, line_no: usize, file_name: FileName) -> ParseError {
+    pub fn into_parse_error(self, line_no: usize, file_name: impl Into<FileName>) -> ParseError {
+        let file_name = file_name.into();
+
         match self {
+            Self::BadCondition => ParseError::BadCondition(line_no, file_name),
+            Self::BadValue => ParseError::BadValue(line_no, file_name),
             Self::MinusInStartOfName => ParseError::MinusInStartOfName(line_no, file_name),
             Self::NoCommand => ParseError::NoCommand(line_no, file_name),
             Self::NoName => ParseError::NoName(line_no, file_name),
+            Self::NotService => ParseError::NotService(line_no, file_name),
             Self::SpaceInName => ParseError::SpaceInName(line_no, file_name),
+            Self::UnknownOption => ParseError::UnknownOption(line_no, file_name),
             Self::UsedName => ParseError::UsedName(line_no, file_name),
         }
     }
@@ -43,25 +127,34 @@ impl FormatError {
 /// The type representing a line number.
 pub type LineNumber = usize;
 
-/// The type representing a line number.
-pub type FileName = &'static str;
+/// The type representing a column number.
+pub type ColumnNumber = usize;
+
+/// The type representing a file name, or the path of a template.
+pub type FileName = Cow<'static, str>;
 
 /// The error type for parsing operations.
 pub enum ParseError {
+    BadCondition(LineNumber, FileName),
+    BadJson(LineNumber, ColumnNumber, FileName),
+    BadValue(LineNumber, FileName),
     MinusInStartOfName(LineNumber, FileName),
     NoName(LineNumber, FileName),
     NoCommand(LineNumber, FileName),
+    NotService(LineNumber, FileName),
     SpaceInName(LineNumber, FileName),
-    NoColon(LineNumber),
-    UnexpectedComment(LineNumber),
+    NoColon(LineNumber, FileName),
+    UnexpectedComment(LineNumber, FileName),
+    UnknownOption(LineNumber, FileName),
     UsedName(LineNumber, FileName),
 }
 
-impl ToString for ParseError {
-    fn to_string(&self) -> String {
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::MinusInStartOfName(line_no, file_name) => {
-                format!(
+                write!(
+                    f,
                     "{}{}{} {} {} {}",
                     "name begins with '".red(),
                     "-".green(),
@@ -72,17 +165,19 @@ impl ToString for ParseError {
                 )
             }
 
-            Self::NoColon(line_no) => {
-                format!(
+            Self::NoColon(line_no, file_name) => {
+                write!(
+                    f,
                     "{} {} {} {}",
                     "split name and script at line".red(),
                     line_no.green(),
                     "in".red(),
-                    "run.yaml".green()
+                    file_name.green()
                 )
             }
             Self::NoCommand(line_no, file_name) => {
-                format!(
+                write!(
+                    f,
                     "{} {} {} {}",
                     "script is empty at line".red(),
                     line_no.green(),
@@ -92,7 +187,8 @@ impl ToString for ParseError {
             }
 
             Self::NoName(line_no, file_name) => {
-                format!(
+                write!(
+                    f,
                     "{} {} {} {}",
                     "name is empty at line".red(),
                     line_no.green(),
@@ -101,8 +197,58 @@ impl ToString for ParseError {
                 )
             }
 
+            Self::BadJson(line_no, column_no, file_name) => {
+                write!(
+                    f,
+                    "{} {}{} {} {} {}",
+                    "invalid JSON at line".red(),
+                    line_no.green(),
+                    ", column".red(),
+                    column_no.green(),
+                    "in".red(),
+                    file_name.green()
+                )
+            }
+
+            Self::BadCondition(line_no, file_name) => {
+                write!(
+                    f,
+                    "{} {} {} {}\n\n{}",
+                    "condition isn't valid at line".red(),
+                    line_no.green(),
+                    "in".red(),
+                    file_name.green(),
+                    "use `port <PORT>`, `file <PATH>`, `http://<URL>`, or `output <REGEX>`"
+                        .yellow()
+                )
+            }
+
+            Self::NotService(line_no, file_name) => {
+                write!(
+                    f,
+                    "{} {} {} {}\n\n{}",
+                    "script with `ready_when` isn't a service at line".red(),
+                    line_no.green(),
+                    "in".red(),
+                    file_name.green(),
+                    "add `service: true` to make it one".yellow()
+                )
+            }
+
+            Self::BadValue(line_no, file_name) => {
+                write!(
+                    f,
+                    "{} {} {} {}",
+                    "option has a bad value at line".red(),
+                    line_no.green(),
+                    "in".red(),
+                    file_name.green()
+                )
+            }
+
             Self::SpaceInName(line_no, file_name) => {
-                format!(
+                write!(
+                    f,
                     "{}{}{} {} {} {}",
                     "name contains '".red(),
                     " ".green(),
@@ -113,17 +259,29 @@ impl ToString for ParseError {
                 )
             }
 
-            Self::UnexpectedComment(line_no) => {
-                format!(
+            Self::UnexpectedComment(line_no, file_name) => {
+                write!(
+                    f,
                     "{} {} {} {}",
                     "unexpected comment at line".red(),
                     line_no.green(),
                     "in".red(),
-                    "run.yaml".green()
+                    file_name.green()
+                )
+            }
+            Self::UnknownOption(line_no, file_name) => {
+                write!(
+                    f,
+                    "{} {} {} {}",
+                    "unknown option at line".red(),
+                    line_no.green(),
+                    "in".red(),
+                    file_name.green()
                 )
             }
             Self::UsedName(line_no, file_name) => {
-                format!(
+                write!(
+                    f,
                     "{} {} {} {}",
                     "same name is already used at line".red(),
                     line_no.green(),
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use crate::{args::Args, beautify::Beautify, date::Date, history, script};
+
+use super::{db::Database, errors::DatabaseError};
+
+/// The number of entries displayed by `run history`, unless `-n` is given.
+const DEFAULT_COUNT: usize = 20;
+
+impl<'a> Database<'a> {
+    /// Prints the latest entries of the history, from the oldest to the newest.
+    ///
+    /// If a filter is given, only the scripts whose names contain it are printed.
+    /// If `--json` is given, the entries are printed as a JSON array.
+    pub fn history(&'a self, args: &'a [String]) -> Result<(), DatabaseError<'a>> {
+        let mut filter = None;
+        let mut count = DEFAULT_COUNT;
+        let mut json = false;
+
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--json" => json = true,
+                "-n" => {
+                    let value = iter.next().map(String::as_str).unwrap_or_default();
+
+                    count = value.parse().map_err(|_| DatabaseError::BadCount(value))?;
+                }
+                _ => filter = Some(arg.as_str()),
+            }
+        }
+
+        let entries = history::read();
+
+        let entries: Vec<_> = entries
+            .iter()
+            .filter(|entry| filter.is_none_or(|filter| entry.name.contains(filter)))
+            .collect();
+
+        let entries = &entries[entries.len().saturating_sub(count)..];
+
+        if json {
+            let objects: Vec<String> = entries
+                .iter()
+                .map(|entry| format!("  {}", entry.to_json()))
+                .collect();
+
+            println!("[\n{}\n]", objects.join(",\n"));
+
+            return Ok(());
+        }
+
+        if entries.is_empty() {
+            return Err(DatabaseError::NoHistory);
+        }
+
+        let mut content = "History:\n".green().to_string();
+
+        for entry in entries {
+            let result = match entry.exit_code {
+                0 => format!(
+                    "{} {}",
+                    "in".green(),
+                    format!("{:.2?}", Duration::from_millis(entry.duration)).yellow()
+                ),
+                exit_code => format!("{} {}", "error code".red(), exit_code.green()),
+            };
+
+            content += &format!(
+                "    {}  {}  {}\n",
+                Date::from_unix_millis(entry.start).to_string().yellow(),
+                script::with_args(&entry.name, &entry.args).green(),
+                result
+            );
+        }
+
+        println!("{}", content);
+
+        Ok(())
+    }
+
+    /// Runs the script which was run directly the last time, with the same script arguments.
+    pub fn last(&'a self, args: &Args) -> Result<i32, DatabaseError<'a>> {
+        let entries = history::read();
+
+        let Some(entry) = entries.iter().rev().find(|entry| entry.direct) else {
+            return Err(DatabaseError::NoHistory);
+        };
+
+        // The script may be renamed or removed since then.
+        let Some((name, _)) = self.scripts().find(|(name, _)| *name == entry.name) else {
+            return Err(DatabaseError::NoLongerExists(entry.name.clone()));
+        };
+
+        self.run(name, &entry.args, args)
+    }
+}
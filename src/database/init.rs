@@ -0,0 +1,507 @@
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{beautify::Beautify, prompt};
+
+use super::{
+    db::{rename_references, Database},
+    errors::{DatabaseError, ParseError},
+    parse::{detect_template, prefix_of, read_template, template_names},
+};
+
+/// How `run -i` treats an existing run.yaml.
+#[derive(Clone, Copy, PartialEq)]
+pub enum InitMode {
+    /// run.yaml must not exist.
+    New,
+    /// run.yaml is overwritten.
+    Force,
+    /// The scripts which aren't in run.yaml are added to it.
+    Merge,
+}
+
+/// The flags given to `run -i`.
+pub struct InitFlags<'f> {
+    /// How an existing run.yaml is treated.
+    pub mode: InitMode,
+    /// The name of the template chosen explicitly, like `rust`.
+    pub template: Option<&'f str>,
+}
+
+impl<'f> InitFlags<'f> {
+    /// Parses the flags given to `run -i`.
+    pub fn parse(flags: &'f [String]) -> Result<InitFlags<'f>, DatabaseError<'f>> {
+        let mut mode = InitMode::New;
+        let mut template = None;
+
+        let mut iter = flags.iter();
+
+        while let Some(flag) = iter.next() {
+            match flag.as_str() {
+                "-f" | "--force" if mode != InitMode::Merge => mode = InitMode::Force,
+                "-m" | "--merge" if mode != InitMode::Force => mode = InitMode::Merge,
+                "-t" | "--template" => match iter.next() {
+                    Some(name) => template = Some(name.as_str()),
+                    None => return Err(DatabaseError::NoTemplateName),
+                },
+                _ => return Err(DatabaseError::BadInitFlag(flag)),
+            }
+        }
+
+        Ok(InitFlags { mode, template })
+    }
+
+    /// Returns the name, the name its errors are reported with, and the content of the template chosen explicitly,
+    /// or else the one of the project if there is no manifest.
+    pub fn template(&self, has_manifests: bool) -> Result<Option<Template<'f>>, DatabaseError<'f>> {
+        let name = match (self.template, has_manifests) {
+            (Some(name), _) => name,
+            (None, false) => match detect_template() {
+                Some(name) => name,
+                None => return Ok(None),
+            },
+            (None, true) => return Ok(None),
+        };
+
+        match read_template(name) {
+            Some((file_name, template)) => Ok(Some((name, file_name, template))),
+            None => Err(DatabaseError::NoTemplate(name, template_names().join(", "))),
+        }
+    }
+}
+
+/// A template with its name, the name its errors are reported with, and its content.
+pub type Template<'f> = (&'f str, String, Cow<'static, str>);
+
+/// How a name used by several manifests is imported.
+enum Resolution {
+    /// Only the script of the manifest with the given index is imported.
+    Keep(usize),
+    /// All the scripts are imported, prefixed with their sources like `npm:build`.
+    Prefix,
+}
+
+/// What `run -i` imported, and from where.
+#[derive(Default)]
+pub struct Summary {
+    /// The manifests or the template, and the numbers of the scripts imported from them.
+    imported: Vec<(String, usize)>,
+    /// The names used by several manifests, and how they are imported.
+    clashes: Vec<(String, String)>,
+    /// The names which are already in run.yaml, so aren't imported.
+    kept: Vec<String>,
+    /// Whether `npm_compat` is enabled, as the scripts of package.json have pre and post scripts.
+    npm_compat: bool,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .imported
+            .iter()
+            .map(|(file_name, _)| file_name.chars().count())
+            .chain(self.clashes.iter().map(|(name, _)| name.chars().count()))
+            .chain(self.kept.iter().map(|name| name.chars().count()))
+            .chain(self.npm_compat.then_some("npm_compat".len()))
+            .max()
+            .unwrap_or(0);
+
+        for (file_name, count) in &self.imported {
+            writeln!(
+                f,
+                "    {}  {}",
+                format!("{:<width$}", file_name, width = width).yellow(),
+                format!("{} scripts imported", count).green()
+            )?;
+        }
+
+        for (name, resolution) in &self.clashes {
+            writeln!(
+                f,
+                "    {}  {}",
+                format!("{:<width$}", name, width = width).yellow(),
+                resolution.green()
+            )?;
+        }
+
+        for name in &self.kept {
+            writeln!(
+                f,
+                "    {}  {}",
+                format!("{:<width$}", name, width = width).yellow(),
+                "is already in run.yaml, kept".green()
+            )?;
+        }
+
+        if self.npm_compat {
+            writeln!(
+                f,
+                "    {}  {}",
+                format!("{:<width$}", "npm_compat", width = width).yellow(),
+                "is enabled to run the pre and post scripts, like npm".green()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Database<'a> {
+    /// Creates a database from the scripts of all the manifests, and then returns it with a summary of what is imported.
+    ///
+    /// The names used by several manifests are asked which manifest to import from if it's interactive,
+    /// or else prefixed with their sources. If the existing run.yaml is given, its scripts and settings are kept.
+    /// If a template is given with its name and the name its errors are reported with,
+    /// its scripts are added before the ones of the manifests.
+    pub fn from_manifests(
+        manifests: &'a [(&'static str, String)],
+        run_yaml: Option<&'a str>,
+        template: Option<(&str, &str, &'a str)>,
+        interactive: bool,
+    ) -> Result<(Database<'a>, Summary), ParseError> {
+        let mut summary = Summary::default();
+
+        let mut db = match run_yaml {
+            Some(run_yaml) => Database::parse_run_yaml(run_yaml)?,
+            None if manifests.is_empty() && template.is_none() => Database::from_example(),
+            None => Database::new(),
+        };
+
+        if let Some((name, file_name, template)) = template {
+            let mut count = 0;
+
+            for (name, script) in
+                Database::parse_yaml(template, file_name.to_string().into())?.into_scripts()
+            {
+                if db.contains(&name) {
+                    summary.kept.push(name.to_string());
+                    continue;
+                }
+
+                // The line of the name of the script, as the errors are told with it.
+                let line_no = template
+                    .lines()
+                    .position(|line| line.trim_start().starts_with(&format!("{}:", name)))
+                    .map_or(1, |index| index + 1);
+
+                db.add(name, script)
+                    .map_err(|err| err.into_parse_error(line_no, file_name.to_string()))?;
+
+                count += 1;
+            }
+
+            summary.imported.push((format!("{} template", name), count));
+        }
+
+        let mut manifest_dbs = vec![];
+
+        for (file_name, content) in manifests {
+            manifest_dbs.push((*file_name, Database::from_manifest(file_name, content)?));
+        }
+
+        let resolutions = resolve_clashes(&manifest_dbs, interactive);
+
+        let file_names: Vec<&str> = manifest_dbs
+            .iter()
+            .map(|(file_name, _)| *file_name)
+            .collect();
+
+        // The names the clashing scripts are imported with, to be told in the summary.
+        let mut clash_names: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for (index, (file_name, manifest_db)) in manifest_dbs.into_iter().enumerate() {
+            let prefix = prefix_of(file_name);
+            let prefixed = |name: &str| format!("{}:{}", prefix, name);
+
+            let own_names: HashSet<String> = manifest_db
+                .scripts()
+                .map(|(name, _)| name.to_string())
+                .collect();
+
+            // A name imported from another manifest is dropped from this one.
+            let is_dropped = |name: &str| matches!(resolutions.get(name), Some(Resolution::Keep(kept)) if *kept != index);
+
+            if manifest_db.settings().npm_compat() && !db.settings().npm_compat() {
+                db.settings_mut().set_npm_compat(true);
+                summary.npm_compat = true;
+            }
+
+            // The references to the clashing names are prefixed, not to run the scripts of the other manifests.
+            let scripts: Vec<_> = manifest_db
+                .into_scripts()
+                .map(|(name, mut script)| {
+                    let dropped = RefCell::new(vec![]);
+
+                    rename_references(&mut script, |reference| {
+                        if !own_names.contains(reference) {
+                            return reference.to_string();
+                        }
+
+                        match resolutions.get(reference) {
+                            Some(Resolution::Prefix) => prefixed(reference),
+                            Some(Resolution::Keep(_)) if is_dropped(reference) => {
+                                dropped.borrow_mut().push(reference.to_string());
+                                prefixed(reference)
+                            }
+                            _ => reference.to_string(),
+                        }
+                    });
+
+                    (name, script, dropped.into_inner())
+                })
+                .collect();
+
+            // The dropped scripts are still imported with the prefix, if the imported ones depend on them.
+            let mut needed = HashSet::new();
+
+            let mut pending: Vec<&str> = scripts
+                .iter()
+                .filter(|(name, _, _)| !is_dropped(name))
+                .flat_map(|(_, _, dropped)| dropped.iter().map(String::as_str))
+                .collect();
+
+            while let Some(name) = pending.pop() {
+                if needed.insert(name) {
+                    if let Some((_, _, dropped)) =
+                        scripts.iter().find(|(other, _, _)| other == name)
+                    {
+                        pending.extend(dropped.iter().map(String::as_str));
+                    }
+                }
+            }
+
+            let needed: HashSet<String> = needed.into_iter().map(str::to_string).collect();
+
+            let mut count = 0;
+
+            for (name, script, _) in scripts {
+                let new_name = match resolutions.get(name.as_ref()) {
+                    Some(Resolution::Prefix) => prefixed(&name),
+                    Some(Resolution::Keep(_)) if is_dropped(&name) => {
+                        match needed.contains(name.as_ref()) {
+                            true => prefixed(&name),
+                            false => continue,
+                        }
+                    }
+                    _ => name.to_string(),
+                };
+
+                if db.contains(&new_name) {
+                    summary.kept.push(new_name);
+                    continue;
+                }
+
+                db.add(new_name.clone(), script)
+                    .map_err(|err| err.into_parse_error(1, file_name))?;
+
+                if resolutions.contains_key(name.as_ref()) {
+                    clash_names
+                        .entry(name.to_string())
+                        .or_default()
+                        .push(new_name);
+                }
+
+                count += 1;
+            }
+
+            summary.imported.push((file_name.to_string(), count));
+        }
+
+        // Only the clashing scripts which are imported are told, the others are already in run.yaml.
+        for (name, mut names) in clash_names {
+            let description = match resolutions.get(&name) {
+                Some(Resolution::Keep(kept)) if names.contains(&name) => {
+                    names.retain(|other| *other != name);
+
+                    match names.is_empty() {
+                        true => format!("imported from {}", file_names[*kept]),
+                        false => format!(
+                            "imported from {}, and as {} for the scripts depending on it",
+                            file_names[*kept],
+                            names.join(" and ")
+                        ),
+                    }
+                }
+                _ => format!("imported as {}", names.join(" and ")),
+            };
+
+            summary.clashes.push((name, description));
+        }
+
+        Ok((db, summary))
+    }
+}
+
+/// Decides how the names used by several manifests are imported.
+///
+/// They are asked if it's interactive, or else they are prefixed with their sources.
+fn resolve_clashes(
+    manifest_dbs: &[(&'static str, Database)],
+    interactive: bool,
+) -> HashMap<String, Resolution> {
+    let mut sources: HashMap<&str, Vec<usize>> = HashMap::new();
+
+    for (index, (_, manifest_db)) in manifest_dbs.iter().enumerate() {
+        for (name, _) in manifest_db.scripts() {
+            sources.entry(name).or_default().push(index);
+        }
+    }
+
+    let mut clashes: Vec<_> = sources
+        .into_iter()
+        .filter(|(_, indexes)| indexes.len() > 1)
+        .collect();
+
+    clashes.sort();
+
+    let mut resolutions = HashMap::new();
+
+    for (name, indexes) in clashes {
+        let file_names: Vec<&str> = indexes.iter().map(|index| manifest_dbs[*index].0).collect();
+
+        let choices: Vec<String> = file_names
+            .iter()
+            .enumerate()
+            .map(|(choice, file_name)| format!("[{}] {}", choice + 1, file_name))
+            .collect();
+
+        let answer = match interactive {
+            true => prompt::ask(
+                name,
+                &format!("is in {}. Import it from", file_names.join(" and ")),
+                &format!("{}, or [P]refix all?", choices.join(", ")),
+            ),
+            false => None,
+        };
+
+        let chosen = answer
+            .and_then(|answer| answer.parse::<usize>().ok())
+            .and_then(|choice| indexes.get(choice.wrapping_sub(1)));
+
+        let resolution = match chosen {
+            Some(index) => Resolution::Keep(*index),
+            None => Resolution::Prefix,
+        };
+
+        resolutions.insert(name.to_string(), resolution);
+    }
+
+    resolutions
+}
+
+/// Returns the message telling how run.yaml is changed.
+pub fn changed_message(mode: InitMode, existed: bool) -> &'static str {
+    match (mode, existed) {
+        (InitMode::Merge, true) => "is updated",
+        (InitMode::Force, true) => "is overwritten",
+        _ => "is generated",
+    }
+}
+
+#[test]
+fn test() {
+    let manifests = [
+        (
+            super::parse::PACKAGE_JSON,
+            r#"{ "scripts": { "build": "tsc", "ci": "npm run build && echo ok", "lint": "eslint ." } }"#
+                .to_string(),
+        ),
+        (
+            super::parse::DENO_JSON,
+            r#"{ "tasks": { "build": "deno compile", "start": "deno run main.ts" } }"#.to_string(),
+        ),
+    ];
+
+    let run_yaml = "lint: cargo clippy\n";
+
+    let (db, summary) = Database::from_manifests(&manifests, Some(run_yaml), None, false)
+        .ok()
+        .unwrap();
+
+    let names: Vec<_> = db.scripts().map(|(name, _)| name).collect();
+
+    // The clashing names must be prefixed, and the scripts of run.yaml must be kept.
+    assert_eq!(names, ["ci", "deno:build", "lint", "npm:build", "start"]);
+    assert_eq!(db.get("lint").ok().unwrap().1.command(), "cargo clippy");
+    assert_eq!(summary.kept, ["lint"]);
+    assert_eq!(
+        summary.imported,
+        [
+            (super::parse::PACKAGE_JSON.to_string(), 2),
+            (super::parse::DENO_JSON.to_string(), 2)
+        ]
+    );
+
+    // The dependencies must be renamed along with them.
+    let (_, ci_script) = db.get("ci").ok().unwrap();
+
+    assert_eq!(ci_script.depends().collect::<Vec<_>>(), ["npm:build"]);
+    assert_eq!(
+        summary.clashes,
+        [(
+            "build".to_string(),
+            "imported as npm:build and deno:build".to_string()
+        )]
+    );
+
+    // The clashing scripts already in run.yaml mustn't be told as imported.
+    let run_yaml = "npm:build: tsc\n\ndeno:build: deno compile\n";
+
+    let (_, summary) = Database::from_manifests(&manifests, Some(run_yaml), None, false)
+        .ok()
+        .unwrap();
+
+    assert!(summary.clashes.is_empty());
+    assert_eq!(summary.kept, ["npm:build", "deno:build"]);
+
+    // The flags must be parsed.
+    let flags = |flags: &[&str]| {
+        let flags: Vec<String> = flags.iter().map(|flag| flag.to_string()).collect();
+        InitFlags::parse(&flags)
+            .ok()
+            .map(|flags| (flags.mode, flags.template.map(str::to_string)))
+    };
+
+    assert!(flags(&[]) == Some((InitMode::New, None)));
+    assert!(flags(&["--merge"]) == Some((InitMode::Merge, None)));
+    assert!(flags(&["-f"]) == Some((InitMode::Force, None)));
+    assert!(flags(&["--force", "--template", "go"]) == Some((InitMode::Force, Some("go".into()))));
+    assert!(flags(&["--force", "--merge"]).is_none());
+    assert!(flags(&["--template"]).is_none());
+    assert!(flags(&["--what"]).is_none());
+
+    // The scripts of the template must be added, except the ones already in run.yaml.
+    let template = "build: go build\n\ntest: go test\n";
+
+    let (db, summary) = Database::from_manifests(
+        &[],
+        Some("test: make\n"),
+        Some(("go", "the go template", template)),
+        false,
+    )
+    .ok()
+    .unwrap();
+
+    assert_eq!(db.get("build").ok().unwrap().1.command(), "go build");
+    assert_eq!(db.get("test").ok().unwrap().1.command(), "make");
+    assert_eq!(summary.imported, [("go template".to_string(), 1)]);
+    assert_eq!(summary.kept, ["test"]);
+    assert!(!summary.npm_compat);
+
+    // Enabling `npm_compat` for the pre and post scripts must be told.
+    let manifests = [(
+        super::parse::PACKAGE_JSON,
+        r#"{ "scripts": { "prebuild": "rm -rf dist", "build": "tsc" } }"#.to_string(),
+    )];
+
+    let (db, summary) = Database::from_manifests(&manifests, None, None, false)
+        .ok()
+        .unwrap();
+
+    assert!(db.settings().npm_compat());
+    assert!(summary.npm_compat);
+}
@@ -0,0 +1,36 @@
+use std::fs;
+
+use crate::{log, service};
+
+use super::{db::Database, errors::DatabaseError};
+
+impl<'a> Database<'a> {
+    /// Prints the logs of the script.
+    ///
+    /// For services, prints the last lines of their logs, and keeps printing if `--follow` is given.
+    /// For other scripts, prints their latest log file.
+    pub fn logs(&'a self, args: &'a [String]) -> Result<(), DatabaseError<'a>> {
+        let follow = args.iter().any(|arg| arg == "-f" || arg == "--follow");
+
+        let aliases_or_names: Vec<&'a String> =
+            args.iter().filter(|arg| !arg.starts_with('-')).collect();
+
+        let [alias_or_name] = aliases_or_names.as_slice() else {
+            return Err(DatabaseError::NoLogName);
+        };
+
+        let (name, script) = self.get(alias_or_name)?;
+
+        if script.service() {
+            return service::print_logs(name, follow).map_err(DatabaseError::Service);
+        }
+
+        match log::latest(name).and_then(|path| fs::read_to_string(path).ok()) {
+            Some(content) => {
+                print!("{}", content);
+                Ok(())
+            }
+            None => Err(DatabaseError::NoLogs(name)),
+        }
+    }
+}
@@ -1,7 +1,14 @@
+mod bench;
 mod db;
+mod doctor;
 mod errors;
+mod history;
+mod init;
+mod logs;
 mod parse;
+mod run;
+mod services;
 
 pub use db::Database;
-
-pub use parse::{DENO_JSON, PACKAGE_JSON, RUN_YAML};
+pub use init::{changed_message, InitFlags, InitMode};
+pub use parse::{read_manifests, read_sources, RUN_YAML};
@@ -1,12 +1,103 @@
-use crate::database::{db::Database, errors::ParseError};
+use std::borrow::Cow;
 
-pub const DENO_JSON: &'static str = "deno.json";
+use crate::{
+    database::{
+        db::Database,
+        errors::{FormatError, ParseError},
+    },
+    json::{self, Value},
+    script::{self, Script},
+};
+
+use super::json::{json_error, object_members, trim};
+
+pub const DENO_JSON: &str = "deno.json";
+
+pub const DENO_JSONC: &str = "deno.jsonc";
 
 impl<'a> Database<'a> {
-    /// Creates a database by searching for script names and commands inside `"tasks"` object inside `deno_json`. 
+    /// Creates a database from the task names and commands inside the top-level `"tasks"` object of `deno_json`.
     pub fn from_deno_json(deno_json: &'a str) -> Result<Database<'a>, ParseError> {
-        Database::from_json_object(deno_json, "tasks", DENO_JSON)
+        Database::from_deno(deno_json, DENO_JSON)
+    }
+
+    /// Creates a database from the task names and commands inside the top-level `"tasks"` object of `deno_jsonc`.
+    pub fn from_deno_jsonc(deno_jsonc: &'a str) -> Result<Database<'a>, ParseError> {
+        Database::from_deno(deno_jsonc, DENO_JSONC)
+    }
+
+    /// Creates a database from the tasks of a Deno configuration file, which may have comments and trailing commas.
+    ///
+    /// The tasks are either commands, or objects having a command, a description, and dependencies.
+    fn from_deno(content: &'a str, file_name: &'static str) -> Result<Database<'a>, ParseError> {
+        // Create a new database.
+        let mut db = Database::new();
+
+        let value = json::parse_jsonc(content).map_err(json_error(file_name))?;
+
+        for (name, task, line_no) in object_members(&value, "tasks", file_name)? {
+            let script =
+                task_to_script(task).map_err(|err| err.into_parse_error(*line_no, file_name))?;
+
+            // Try to add the task name and script to the database.
+            db.add(trim(name), script)
+                .map_err(|err| err.into_parse_error(*line_no, file_name))?;
+        }
+
+        // Succesfully return the database.
+        Ok(db)
+    }
+}
+
+/// Converts a task, which is either a command or an object, to a script.
+fn task_to_script<'a>(task: &Value<'a>) -> Result<Script<'a>, FormatError> {
+    let members = match task {
+        Value::String(command) => return Ok(Script::new(trim(command))),
+        Value::Object(members) => members,
+        _ => return Err(FormatError::BadValue),
+    };
+
+    let mut command = Cow::Borrowed("");
+    let mut description = Cow::Borrowed("");
+    let mut dependencies = vec![];
+    let mut cwd = None;
+
+    for (key, value, _) in members {
+        match (key.as_ref(), value) {
+            ("command", Value::String(value)) => command = trim(value),
+            ("description", Value::String(value)) => description = trim(value),
+            ("dependencies", Value::Array(values)) => {
+                for value in values {
+                    match value.as_str() {
+                        Some(dependency) => dependencies.push(dependency.trim().to_string()),
+                        None => return Err(FormatError::BadValue),
+                    }
+                }
+            }
+            ("cwd", Value::String(value)) => cwd = Some(trim(value)),
+            ("command" | "description" | "dependencies" | "cwd", _) => {
+                return Err(FormatError::BadValue)
+            }
+            // The other keys of the task aren't supported by run, so they are ignored like Deno ignores the unknown ones.
+            _ => (),
+        }
+    }
+
+    // The task is executed in its directory, which is quoted like the arguments of the scripts.
+    if let Some(cwd) = cwd.filter(|_| !command.is_empty()) {
+        command = Cow::Owned(format!("cd {} && {}", script::quote(&cwd), command));
+    }
+
+    let mut script = Script::new_with_comment(command, description);
+
+    if !dependencies.is_empty() {
+        script.set_depends(dependencies.join(", "));
     }
+
+    // Deno runs the dependencies of a task at the same time.
+    script.set_parallel(dependencies.len() > 1);
+
+    Ok(script)
 }
 
 #[test]
@@ -33,10 +124,10 @@ fn test() {
     assert_eq!(scripts_and_names.len(), 1);
 
     // Get first script.
-    let (start_name, start_script) = scripts_and_names.get(0).unwrap();
+    let (start_name, start_script) = scripts_and_names.first().unwrap();
 
     // Check names.
-    assert_eq!(start_name, &&"start");
+    assert_eq!(start_name, &"start");
 
     // Check commands.
     assert_eq!(
@@ -44,5 +135,78 @@ fn test() {
         "deno run -A --watch=static/,routes/ dev.ts"
     );
 
-    // As deno.json scripts doesn't have comments, we don't have to check them.
+    // The tasks written as strings can't have a description, so they must have the default comment.
+    assert_eq!(start_script.comment(), "This script has no comment.");
+
+    // Comments, trailing commas, and tasks written as objects must be supported.
+    let deno_jsonc = r#"{
+        // The tasks of the project.
+        "tasks": {
+            "check": "deno check main.ts",
+            "lint": { "command": "deno lint", },
+            /* Builds after checking. */
+            "build": {
+                "description": "Builds the \"app\".",
+                "command": "deno compile main.ts",
+                "dependencies": ["check", "lint"],
+            },
+            "ci": { "dependencies": ["build"] },
+        },
+    }"#;
+
+    let db = Database::from_deno_jsonc(deno_jsonc).ok().unwrap();
+    let scripts_and_names = db.scripts_and_names();
+
+    assert_eq!(scripts_and_names.len(), 4);
+
+    let (build_name, build_script) = scripts_and_names.first().unwrap();
+
+    assert_eq!(build_name, &"build");
+    assert_eq!(build_script.command(), "deno compile main.ts");
+    assert_eq!(build_script.comment(), "Builds the \"app\".");
+    assert_eq!(
+        build_script.depends().collect::<Vec<_>>(),
+        ["check", "lint"]
+    );
+
+    // A task may only have dependencies.
+    let (ci_name, ci_script) = scripts_and_names.get(2).unwrap();
+
+    assert_eq!(ci_name, &"ci");
+    assert_eq!(ci_script.command(), "");
+    assert_eq!(ci_script.depends().collect::<Vec<_>>(), ["build"]);
+
+    // The dependencies must be run at the same time, like Deno does.
+    assert!(build_script.parallel());
+    assert!(!ci_script.parallel());
+
+    // The directory of a task must be kept, and the unsupported keys must be ignored.
+    let db = Database::from_deno_jsonc(
+        r#"{ "tasks": { "a": { "command": "x", "cwd": "src", "what": true } } }"#,
+    )
+    .ok()
+    .unwrap();
+
+    let (_, a_script) = db.scripts_and_names().remove(0);
+
+    assert_eq!(a_script.command(), "cd src && x");
+
+    // The directory must be quoted, so the shell doesn't expand it.
+    let db = Database::from_deno_jsonc(
+        r#"{ "tasks": { "a": { "command": "x", "cwd": "it's \"$HOME\"" } } }"#,
+    )
+    .ok()
+    .unwrap();
+
+    let (_, a_script) = db.scripts_and_names().remove(0);
+
+    if !cfg!(target_os = "windows") {
+        assert_eq!(a_script.command(), "cd 'it'\\''s \"$HOME\"' && x");
+    }
+
+    // The known keys with bad values must still be rejected.
+    assert!(matches!(
+        Database::from_deno_jsonc(r#"{ "tasks": { "a": { "command": 1 } } }"#),
+        Err(ParseError::BadValue(1, file_name)) if file_name == DENO_JSONC
+    ));
 }
@@ -1,9 +1,18 @@
 mod deno_json;
 mod example;
 mod json;
+mod npm;
 mod package_json;
 mod run_yaml;
+mod sources;
+mod templates;
 
-pub use deno_json::DENO_JSON;
+pub use deno_json::{DENO_JSON, DENO_JSONC};
 pub use package_json::PACKAGE_JSON;
-pub use run_yaml::RUN_YAML;
\ No newline at end of file
+pub use run_yaml::RUN_YAML;
+pub use sources::{read_manifests, read_sources};
+pub use templates::{detect_template, read_template, template_names};
+
+pub(super) use npm::rename_runs;
+pub(super) use run_yaml::{options_of, saved_command, settings_of, SETTINGS};
+pub(super) use sources::prefix_of;
@@ -0,0 +1,471 @@
+/// The executable of run in the rewritten commands, which is given to the scripts as the `RUN` environment variable.
+///
+/// It's used instead of `run`, which may not be on `PATH`, or may be another program.
+pub(in crate::database) const RUN: &str = if cfg!(target_os = "windows") {
+    "\"%RUN%\""
+} else {
+    "\"$RUN\""
+};
+
+/// A script rewritten without the package managers, to run the other scripts natively.
+pub(super) struct Rewrite {
+    /// The command of the script, which is empty if the script only runs its dependencies.
+    pub command: String,
+    /// The names of the scripts to be run before the command.
+    pub depends: Vec<String>,
+    /// Whether the dependencies are run at the same time.
+    pub parallel: bool,
+}
+
+/// A part of a command, which is separated from the others by `&&`.
+enum Part<'c> {
+    /// Runs a script with the arguments, like `npm run build -- --watch`.
+    Reference(String, Vec<&'c str>),
+    /// Runs the scripts in order, like `run-s lint test`.
+    Sequence(Vec<String>),
+    /// Runs the scripts at the same time, like `run-p watch:*` or `concurrently "npm:a" "npm:b"`.
+    Parallel(Vec<String>),
+    /// Any other command.
+    Other,
+}
+
+/// The commands of the package managers, which aren't scripts even if a script has the same name.
+const BUILTINS: [&str; 16] = [
+    "add", "audit", "bin", "create", "dlx", "exec", "i", "info", "init", "install", "link", "pack",
+    "publish", "remove", "unlink", "upgrade",
+];
+
+/// Rewrites the command referencing the other scripts through the package managers, `run-s`, `run-p`, or `concurrently`.
+///
+/// The references at the beginning become dependencies, and the others are run with `RUN`.
+/// Returns `None` if the command doesn't reference any of the given scripts,
+/// or if it has other operators than `&&`, like `||`, which can't be kept when the references become dependencies.
+pub(super) fn rewrite(command: &str, names: &[&str]) -> Option<Rewrite> {
+    let parts = split(command, "&&")?;
+
+    if parts.iter().any(|part| has_operator(part)) {
+        return None;
+    }
+
+    let parts = parts
+        .into_iter()
+        .map(|text| parse_part(text, names).map(|part| (text, part)))
+        .collect::<Option<Vec<_>>>()?;
+
+    if parts.iter().all(|(_, part)| matches!(part, Part::Other)) {
+        return None;
+    }
+
+    // A single parallel group becomes parallel dependencies.
+    if let [(_, Part::Parallel(scripts))] = parts.as_slice() {
+        return Some(Rewrite {
+            command: String::new(),
+            depends: scripts.clone(),
+            parallel: true,
+        });
+    }
+
+    let mut depends = vec![];
+    let mut rest = parts.as_slice();
+
+    while let Some(((_, part), others)) = rest.split_first() {
+        match part {
+            Part::Reference(name, args) if args.is_empty() => depends.push(name.clone()),
+            Part::Sequence(scripts) => depends.extend(scripts.iter().cloned()),
+            _ => break,
+        }
+
+        rest = others;
+    }
+
+    let command = rest
+        .iter()
+        .map(|(text, part)| match part {
+            Part::Reference(name, args) if args.is_empty() => format!("{} {}", RUN, name),
+            Part::Reference(name, args) => format!("{} {} {}", RUN, name, args.join(" ")),
+            Part::Sequence(scripts) => scripts
+                .iter()
+                .map(|name| format!("{} {}", RUN, name))
+                .collect::<Vec<_>>()
+                .join(" && "),
+            // There is no way to run scripts at the same time in the middle of a command.
+            Part::Parallel(_) | Part::Other => text.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" && ");
+
+    Some(Rewrite {
+        command,
+        depends,
+        parallel: false,
+    })
+}
+
+/// Renames the scripts run with `RUN` in the command written by `rewrite`, then returns the new command.
+///
+/// Returns `None` if the command doesn't run any script with `RUN`.
+pub(in crate::database) fn rename_runs(
+    command: &str,
+    rename: impl Fn(&str) -> String,
+) -> Option<String> {
+    let parts = split(command, "&&")?;
+
+    let run_prefix = format!("{} ", RUN);
+
+    if !parts.iter().any(|part| part.starts_with(&run_prefix)) {
+        return None;
+    }
+
+    let parts: Vec<String> = parts
+        .into_iter()
+        .map(|part| match part.strip_prefix(&run_prefix) {
+            Some(rest) => match rest.split_once(' ') {
+                Some((name, args)) => format!("{}{} {}", run_prefix, rename(name), args),
+                None => format!("{}{}", run_prefix, rename(rest)),
+            },
+            None => part.to_string(),
+        })
+        .collect();
+
+    Some(parts.join(" && "))
+}
+
+/// Returns `true` if the part of a command has a shell operator outside quotes, like `||`, `;`, `|`, or `&`.
+fn has_operator(part: &str) -> bool {
+    let mut quote = None;
+
+    for ch in part.chars() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => (),
+            None if ch == '\'' || ch == '"' => quote = Some(ch),
+            None if matches!(ch, '|' | ';' | '&' | '\n') => return true,
+            None => (),
+        }
+    }
+
+    false
+}
+
+/// Parses a part of a command.
+///
+/// Returns `None` if the part can't be read, like an unmatched quote.
+fn parse_part<'c>(part: &'c str, names: &[&str]) -> Option<Part<'c>> {
+    let tokens = tokenize(part)?;
+
+    let part = parse_reference(&tokens, names)
+        .map(|(name, args)| Part::Reference(name, args))
+        .or_else(|| parse_run_all(&tokens, names))
+        .or_else(|| parse_concurrently(&tokens, names))
+        .unwrap_or(Part::Other);
+
+    Some(part)
+}
+
+/// Parses a command running a script through a package manager, like `yarn build`, and returns the name and the arguments.
+fn parse_reference<'c>(tokens: &[Token<'c>], names: &[&str]) -> Option<(String, Vec<&'c str>)> {
+    let values: Vec<&str> = tokens.iter().map(|token| token.value.as_str()).collect();
+
+    let (name, rest) = match values.as_slice() {
+        ["npm", "run" | "run-script", name, ..] => (*name, &tokens[3..]),
+        ["npm", "test" | "t", ..] => ("test", &tokens[2..]),
+        ["npm", name @ ("start" | "stop" | "restart"), ..] => (*name, &tokens[2..]),
+        ["yarn" | "pnpm" | "bun", "run", name, ..] => (*name, &tokens[3..]),
+        ["yarn" | "pnpm", name, ..] if !BUILTINS.contains(name) => (*name, &tokens[2..]),
+        _ => return None,
+    };
+
+    if !names.contains(&name) {
+        return None;
+    }
+
+    let args = match rest.split_first() {
+        Some((separator, args)) if separator.value == "--" => args,
+        // The arguments before `--` are options of npm.
+        Some(_) if values[0] == "npm" => return None,
+        _ => rest,
+    };
+
+    Some((
+        name.to_string(),
+        args.iter().map(|token| token.raw).collect(),
+    ))
+}
+
+/// Parses a command of `npm-run-all`, like `run-s lint test` or `run-p watch:*`.
+fn parse_run_all<'c>(tokens: &[Token], names: &[&str]) -> Option<Part<'c>> {
+    let (program, rest) = tokens.split_first()?;
+
+    let (parallel, patterns) = match (program.value.as_str(), rest.first()) {
+        ("run-s", _) => (false, rest),
+        ("run-p", _) => (true, rest),
+        ("npm-run-all", Some(flag))
+            if ["-s", "--sequential", "--serial"].contains(&&*flag.value) =>
+        {
+            (false, &rest[1..])
+        }
+        ("npm-run-all", Some(flag)) if ["-p", "--parallel"].contains(&&*flag.value) => {
+            (true, &rest[1..])
+        }
+        ("npm-run-all", _) => (false, rest),
+        _ => return None,
+    };
+
+    let scripts = expand_all(patterns.iter().map(|token| token.value.as_str()), names)?;
+
+    match parallel {
+        true => Some(Part::Parallel(scripts)),
+        false => Some(Part::Sequence(scripts)),
+    }
+}
+
+/// Parses a command of `concurrently` running the scripts, like `concurrently "npm:watch-*" "npm run serve"`.
+fn parse_concurrently<'c>(tokens: &[Token], names: &[&str]) -> Option<Part<'c>> {
+    let (program, commands) = tokens.split_first()?;
+
+    if program.value != "concurrently" {
+        return None;
+    }
+
+    let mut scripts = vec![];
+
+    for command in commands {
+        let value = command.value.as_str();
+
+        let pattern = ["npm:", "yarn:", "pnpm:", "bun:"]
+            .iter()
+            .find_map(|prefix| value.strip_prefix(prefix));
+
+        match pattern {
+            Some(pattern) => scripts.extend(expand_all([pattern].into_iter(), names)?),
+            None => match parse_reference(&tokenize(value)?, names)? {
+                (name, args) if args.is_empty() => scripts.push(name),
+                _ => return None,
+            },
+        }
+    }
+
+    Some(scripts)
+        .filter(|scripts| !scripts.is_empty())
+        .map(Part::Parallel)
+}
+
+/// Expands the patterns to the names of the scripts, in the order they are defined.
+///
+/// `*` matches any characters except `:`, and `**` matches any characters, at the end of a pattern.
+/// Returns `None` if there is no pattern, an option is given, or a pattern doesn't match any script.
+fn expand_all<'p>(patterns: impl Iterator<Item = &'p str>, names: &[&str]) -> Option<Vec<String>> {
+    let mut scripts = vec![];
+
+    for pattern in patterns {
+        if pattern.starts_with('-') {
+            return None;
+        }
+
+        let matches: Vec<String> = match pattern.split_once('*') {
+            None => vec![pattern],
+            Some((prefix, "*")) => names
+                .iter()
+                .copied()
+                .filter(|name| name.starts_with(prefix))
+                .collect(),
+            Some((prefix, "")) => names
+                .iter()
+                .copied()
+                .filter(|name| name.starts_with(prefix) && !name[prefix.len()..].contains(':'))
+                .collect(),
+            Some(_) => return None,
+        }
+        .into_iter()
+        .filter(|name| names.contains(name))
+        .map(str::to_string)
+        .collect();
+
+        if matches.is_empty() {
+            return None;
+        }
+
+        scripts.extend(matches);
+    }
+
+    Some(scripts).filter(|scripts| !scripts.is_empty())
+}
+
+/// A word of a command, with its original text and its value without the quotes.
+struct Token<'c> {
+    raw: &'c str,
+    value: String,
+}
+
+/// Splits the command into words, like a shell.
+///
+/// Returns `None` if there is an unmatched quote or an escape, which aren't supported.
+fn tokenize(command: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = vec![];
+    let mut start = None;
+    let mut value = String::new();
+    let mut quote = None;
+
+    for (index, ch) in command.char_indices() {
+        match (quote, ch) {
+            (_, '\\') => return None,
+            (Some(q), ch) if ch == q => quote = None,
+            (Some(_), ch) => value.push(ch),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                start.get_or_insert(index);
+            }
+            (None, ch) if ch.is_whitespace() => {
+                if let Some(start) = start.take() {
+                    let value = std::mem::take(&mut value);
+                    tokens.push(Token {
+                        raw: &command[start..index],
+                        value,
+                    });
+                }
+            }
+            (None, ch) => {
+                value.push(ch);
+                start.get_or_insert(index);
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return None;
+    }
+
+    if let Some(start) = start {
+        tokens.push(Token {
+            raw: &command[start..],
+            value,
+        });
+    }
+
+    Some(tokens)
+}
+
+/// Splits the command by the separator, except inside quotes, and trims the parts.
+///
+/// Returns `None` if a part is empty.
+fn split<'c>(command: &'c str, separator: &str) -> Option<Vec<&'c str>> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut quote = None;
+
+    for (index, ch) in command.char_indices() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => (),
+            None if ch == '\'' || ch == '"' => quote = Some(ch),
+            None if index >= start && command[index..].starts_with(separator) => {
+                parts.push(command[start..index].trim());
+                start = index + separator.len();
+            }
+            None => (),
+        }
+    }
+
+    parts.push(command[start..].trim());
+
+    Some(parts).filter(|parts| parts.iter().all(|part| !part.is_empty()))
+}
+
+#[test]
+fn test() {
+    let names = [
+        "build",
+        "dev",
+        "lint",
+        "test",
+        "watch:css",
+        "watch:js",
+        "watch:js:fast",
+    ];
+
+    let rewrite = |command| {
+        rewrite(command, &names).map(|rewrite| {
+            (
+                rewrite.command,
+                rewrite.depends.join(", "),
+                rewrite.parallel,
+            )
+        })
+    };
+
+    let own = |command: &str, depends: &str, parallel| {
+        Some((command.to_string(), depends.to_string(), parallel))
+    };
+
+    // References at the beginning must become dependencies.
+    assert_eq!(rewrite("npm run build"), own("", "build", false));
+    assert_eq!(
+        rewrite("yarn lint && pnpm test && vite"),
+        own("vite", "lint, test", false)
+    );
+    assert_eq!(
+        rewrite("npm test && bun run build"),
+        own("", "test, build", false)
+    );
+
+    // The others must be run with `RUN`, with their arguments.
+    assert_eq!(
+        rewrite("npm run build -- --watch"),
+        own(&format!("{} build --watch", RUN), "", false)
+    );
+    assert_eq!(
+        rewrite("tsc && yarn test 'a b'"),
+        own(&format!("tsc && {} test 'a b'", RUN), "", false)
+    );
+    assert_eq!(
+        rewrite("run-s lint build && node . && run-s test"),
+        own(&format!("node . && {} test", RUN), "lint, build", false)
+    );
+
+    // Parallel groups must become parallel dependencies.
+    assert_eq!(
+        rewrite("run-p watch:*"),
+        own("", "watch:css, watch:js", true)
+    );
+    assert_eq!(
+        rewrite("npm-run-all -p dev watch:**"),
+        own("", "dev, watch:css, watch:js, watch:js:fast", true)
+    );
+    assert_eq!(
+        rewrite(r#"concurrently "npm:watch:*" "npm run dev""#),
+        own("", "watch:css, watch:js, dev", true)
+    );
+
+    // Anything else must be kept.
+    assert_eq!(rewrite("npm run missing"), None);
+    assert_eq!(rewrite("npm run --silent build"), None);
+    assert_eq!(rewrite("yarn install"), None);
+    assert_eq!(rewrite("run-p --race dev build"), None);
+    assert_eq!(rewrite("concurrently -k \"npm:dev\""), None);
+    assert_eq!(rewrite("echo 'npm run build && x'"), None);
+
+    // The commands with other operators must be kept, as their references can't become dependencies.
+    assert_eq!(rewrite("yarn test && yarn lint || echo failed"), None);
+    assert_eq!(rewrite("npm run build; echo done"), None);
+    assert_eq!(rewrite("npm run build | tee out.log"), None);
+    assert_eq!(rewrite("npm run dev & npm run build"), None);
+    assert_eq!(
+        rewrite("npm run build && echo 'a || b'"),
+        own("echo 'a || b'", "build", false)
+    );
+
+    // The scripts run with `RUN` must be renamed.
+    let prefix = |name: &str| format!("npm:{}", name);
+
+    assert_eq!(
+        rename_runs(
+            &format!("tsc && {} test --watch && {} lint", RUN, RUN),
+            prefix
+        ),
+        Some(format!(
+            "tsc && {} npm:test --watch && {} npm:lint",
+            RUN, RUN
+        ))
+    );
+    assert_eq!(rename_runs("tsc && vite", prefix), None);
+}
@@ -1,14 +1,127 @@
-use crate::database::{db::Database, errors::ParseError};
+use std::borrow::Cow;
 
-pub const PACKAGE_JSON: &'static str = "package.json";
+use crate::{
+    database::{
+        db::Database,
+        errors::{FormatError, ParseError},
+    },
+    json::{self, Value},
+    script::Script,
+};
+
+use super::{
+    json::{json_error, object_members, trim},
+    npm,
+};
+
+pub const PACKAGE_JSON: &str = "package.json";
 
 impl<'a> Database<'a> {
-    /// Creates a database by searching for script names and commands inside `"scripts"` object inside `package_json`. 
+    /// Creates a database from the script names and commands inside the top-level `"scripts"` object of `package_json`.
+    ///
+    /// The descriptions of the scripts are imported as comments, from `"scripts-info"`, `"ntl": { "descriptions" }`,
+    /// or the `"//"` keys inside `"scripts"`, which describe the scripts following them.
     pub fn from_package_json(package_json: &'a str) -> Result<Database<'a>, ParseError> {
-        Database::from_json_object(package_json, "scripts", PACKAGE_JSON)
+        // Create a new database.
+        let mut db = Database::new();
+
+        let value = json::parse(package_json).map_err(json_error(PACKAGE_JSON))?;
+
+        let scripts_info = value.get("scripts-info").map(|(info, _)| info);
+
+        let ntl_descriptions = value
+            .get("ntl")
+            .and_then(|(ntl, _)| ntl.get("descriptions"))
+            .map(|(descriptions, _)| descriptions);
+
+        let members = object_members(&value, "scripts", PACKAGE_JSON)?;
+
+        // The names of the scripts, in the order they are defined, to find the references to them.
+        let names: Vec<&str> = members
+            .iter()
+            .map(|(name, _, _)| name.trim())
+            .filter(|name| *name != "//")
+            .collect();
+
+        // The description given by the last `"//"` key, which belongs to the next script.
+        let mut comment_key = None;
+
+        for (name, command, line_no) in members {
+            if name == "//" {
+                comment_key = description_of(command);
+                continue;
+            }
+
+            let Some(command) = command.as_str() else {
+                return Err(FormatError::BadValue.into_parse_error(*line_no, PACKAGE_JSON));
+            };
+
+            let name = trim(name);
+
+            let description_in = |descriptions: Option<&Value<'a>>| {
+                descriptions
+                    .and_then(|descriptions| descriptions.get(&name))
+                    .and_then(|(description, _)| description_of(description))
+            };
+
+            let comment = description_in(scripts_info)
+                .or_else(|| description_in(ntl_descriptions))
+                .or(comment_key.take())
+                .unwrap_or_default();
+
+            let mut script = Script::new_with_comment(trim(command), comment);
+
+            // The references to the other scripts are run natively, instead of through the package managers.
+            if let Some(rewrite) = npm::rewrite(script.command(), &names) {
+                script.set_command(rewrite.command);
+
+                if !rewrite.depends.is_empty() {
+                    script.set_depends(rewrite.depends.join(", "));
+                }
+
+                script.set_parallel(rewrite.parallel);
+            }
+
+            // Try to add the script name and script to the database.
+            db.add(name, script)
+                .map_err(|err| err.into_parse_error(*line_no, PACKAGE_JSON))?;
+        }
+
+        // The `pre` and `post` scripts are only run in npm compatibility mode.
+        let has_hooks = names.iter().any(|name| {
+            ["pre", "post"].iter().any(|prefix| {
+                name.strip_prefix(prefix)
+                    .is_some_and(|name| names.contains(&name))
+            })
+        });
+
+        db.settings_mut().set_npm_compat(has_hooks);
+
+        // Succesfully return the database.
+        Ok(db)
     }
 }
 
+/// Returns the description in a string, or in an array of strings which are the lines of it.
+///
+/// The lines are joined into a single line by `Script::new_with_comment`, like the other comments.
+fn description_of<'a>(value: &Value<'a>) -> Option<Cow<'a, str>> {
+    let description = match value {
+        Value::String(description) => trim(description),
+        Value::Array(lines) => Cow::Owned(
+            lines
+                .iter()
+                .filter_map(Value::as_str)
+                .map(Cow::as_ref)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        _ => return None,
+    };
+
+    Some(description).filter(|description| !description.trim().is_empty())
+}
+
 #[test]
 fn test() {
     // Create a test content.
@@ -21,7 +134,8 @@ fn test() {
         },
         "peerDependencies": {
             "react": "^18.2.0"
-        }"#;
+        }
+    }"#;
 
     // Generate a database from package.json content.
     let db = Database::from_package_json(package_json).ok().unwrap();
@@ -33,18 +147,83 @@ fn test() {
     assert_eq!(scripts_and_names.len(), 2);
 
     // Get first script.
-    let (bundle_name, bundle_script) = scripts_and_names.get(0).unwrap();
+    let (bundle_name, bundle_script) = scripts_and_names.first().unwrap();
 
     // Get second script.
     let (compile_name, compile_script) = scripts_and_names.get(1).unwrap();
 
     // Check names.
-    assert_eq!(bundle_name, &&"bundle");
-    assert_eq!(compile_name, &&"compile");
+    assert_eq!(bundle_name, &"bundle");
+    assert_eq!(compile_name, &"compile");
 
     // Check commands.
     assert_eq!(bundle_script.command(), "rollup -c");
     assert_eq!(compile_script.command(), "tsc");
 
-    // As package.json scripts doesn't have comments, we don't have to check them.
+    // The scripts without a description must have the default comment.
+    assert_eq!(bundle_script.comment(), "This script has no comment.");
+
+    // Escapes must be unescaped, and only the top-level `"scripts"` must be used.
+    let package_json = r#"{
+        "config": { "scripts": { "wrong": "nested" } },
+        "scripts": { "lint": "eslint \"src/**/*.ts\" --ext .ts\u0020" }
+    }"#;
+
+    let db = Database::from_package_json(package_json).ok().unwrap();
+    let scripts_and_names = db.scripts_and_names();
+
+    assert_eq!(scripts_and_names.len(), 1);
+
+    let (lint_name, lint_script) = scripts_and_names.first().unwrap();
+
+    assert_eq!(lint_name, &"lint");
+    assert_eq!(lint_script.command(), "eslint \"src/**/*.ts\" --ext .ts");
+
+    // Syntax errors must be found.
+    assert!(matches!(
+        Database::from_package_json("{\n  \"scripts\": {\n    \"a\": \"b\"\n  }\n,}"),
+        Err(ParseError::BadJson(5, 2, file_name)) if file_name == PACKAGE_JSON
+    ));
+
+    // Descriptions must be imported from the common conventions.
+    let package_json = r#"{
+        "scripts": {
+            "//": "Builds the app.",
+            "build": "vite build",
+            "dev": "vite",
+            "//": ["Runs the tests,", "with coverage."],
+            "test": "vitest --coverage",
+            "lint": "eslint ."
+        },
+        "scripts-info": { "dev": "Starts the\r\n dev server.\u0007" },
+        "ntl": { "descriptions": { "dev": "Overridden.", "lint": "Lints the code." } }
+    }"#;
+
+    let db = Database::from_package_json(package_json).ok().unwrap();
+    let scripts_and_names = db.scripts_and_names();
+
+    assert_eq!(scripts_and_names.len(), 4);
+
+    let comments: Vec<_> = scripts_and_names
+        .iter()
+        .map(|(_, script)| script.comment())
+        .collect();
+
+    assert_eq!(
+        comments,
+        [
+            "Builds the app.",
+            "Starts the dev server.",
+            "Lints the code.",
+            "Runs the tests, with coverage."
+        ]
+    );
+
+    // The npm compatibility mode must be enabled for the `pre` and `post` scripts.
+    assert!(!db.settings().npm_compat());
+
+    let package_json = r#"{ "scripts": { "build": "tsc", "postbuild": "node ." } }"#;
+    let db = Database::from_package_json(package_json).ok().unwrap();
+
+    assert!(db.settings().npm_compat());
 }
@@ -1,86 +1,454 @@
+use std::borrow::Cow;
+
 use crate::{
-    database::{db::Database, errors::ParseError},
+    database::{
+        db::Database,
+        errors::{FileName, FormatError, ParseError},
+    },
+    events, json,
+    ready::{self, Condition},
     script::Script,
+    settings::Settings,
 };
 
-pub const RUN_YAML: &'static str = "run.yaml";
+pub const RUN_YAML: &str = "run.yaml";
+
+/// The name of the block holding the settings of run.
+pub const SETTINGS: &str = ".settings";
+
+/// A block of options, which are indented under a name.
+enum Block<'a> {
+    /// The options of the script with the name, and the line number of the name.
+    Script(&'a str, Box<Script<'a>>, usize),
+    /// The settings of run.
+    Settings,
+}
 
 impl<'a> Database<'a> {
+    /// Creates a database from `run_yaml`, then formats and saves run.yaml if it isn't formatted.
     pub fn from_run_yaml(run_yaml: &'a str) -> Result<Database<'a>, ParseError> {
+        let db = Database::parse_run_yaml(run_yaml)?;
+
+        // If current run.yaml content is bad, format it and save.
+        db.save_if_bad(run_yaml);
+
+        events::emit(
+            "database_loaded",
+            &[
+                ("file", json::string(RUN_YAML)),
+                ("scripts", db.scripts().count().to_string()),
+            ],
+        );
+
+        Ok(db)
+    }
+
+    /// Creates a database from content in the format of run.yaml, without changing any file.
+    pub(in crate::database) fn parse_run_yaml(
+        run_yaml: &'a str,
+    ) -> Result<Database<'a>, ParseError> {
+        Database::parse_yaml(run_yaml, RUN_YAML.into())
+    }
+
+    /// Creates a database from content in the format of run.yaml, with the file name its errors are reported in.
+    pub(in crate::database) fn parse_yaml(
+        run_yaml: &'a str,
+        file_name: FileName,
+    ) -> Result<Database<'a>, ParseError> {
         // Create a new database.
         let mut db = Database::new();
 
         // Define a variable to hold the last comment inside run.yaml.
         let mut last_comment: Option<&'a str> = None;
 
+        // Define a variable to hold the block whose options are being read, with the indentation of its name.
+        let mut block: Option<(Block<'a>, usize)> = None;
+
         // Get each line and line index.
-        for (line_index, line) in run_yaml.lines().enumerate() {
+        for (line_index, raw_line) in run_yaml.lines().enumerate() {
             // Remove leading and trailing whitespaces.
-            let line = line.trim();
-
-            // Only continue if line is not empty.
-            if !line.is_empty() {
-                // If a line start with sharp symbol, that means it is a comment.
-                if line.starts_with("#") {
-                    // Set `last_comment` if there isn't `Some(comment)` inside.
-                    if last_comment.is_none() {
-                        last_comment = Some(&line[1..].trim());
+            let line = raw_line.trim();
+
+            // Skip the line if it is empty.
+            if line.is_empty() {
+                continue;
+            }
+
+            // Get the number of leading whitespaces.
+            let indentation = raw_line.len() - raw_line.trim_start().len();
+
+            // If the line is indented more than the name of the block being read, it holds an option of that block.
+            if let Some((block, block_indentation)) = &mut block {
+                if indentation > *block_indentation {
+                    // Options can't have comments.
+                    if line.starts_with('#') {
+                        return Err(ParseError::UnexpectedComment(line_index + 1, file_name));
                     }
-                    // There is an unexpected comment.
-                    else {
-                        return Err(ParseError::UnexpectedComment(line_index + 1));
+
+                    // Try to split the line into an option key and an option value.
+                    let result = match line.split_once(':') {
+                        Some((key, value)) => match block {
+                            Block::Script(_, script, _) => {
+                                set_option(script, key.trim(), value.trim())
+                            }
+                            Block::Settings => {
+                                set_setting(db.settings_mut(), key.trim(), value.trim())
+                            }
+                        },
+                        None => return Err(ParseError::NoColon(line_index + 1, file_name)),
+                    };
+
+                    if let Err(err) = result {
+                        return Err(err.into_parse_error(line_index + 1, file_name));
                     }
+
+                    continue;
+                }
+            }
+
+            // The line isn't an option, so try to add the script being read into database.
+            if let Some((Block::Script(name, script, line_no), _)) = block.take() {
+                if let Err(err) = db.add(name, *script) {
+                    return Err(err.into_parse_error(line_no, file_name));
                 }
-                // If a line isn't a comment, it must hold a script name and script command.
+            }
+
+            // If a line start with sharp symbol, that means it is a comment.
+            if let Some(comment) = line.strip_prefix('#') {
+                // Set `last_comment` if there isn't `Some(comment)` inside.
+                if last_comment.is_none() {
+                    last_comment = Some(comment.trim());
+                }
+                // There is an unexpected comment.
                 else {
-                    // Try to split the line.
-                    match line.split_once(": ") {
-                        // If the splition is succesfull, continue.
-                        Some((key, value)) => {
-                            // Remove leading and trailing whitespaces.
-                            let name = key.trim();
-                            let command = value.trim();
-
-                            // Create a new `Script`.
-                            let script = match last_comment {
-                                Some(comment) => Script::new_with_comment(command, comment),
-                                None => Script::new(command),
-                            };
-
-                            // It has been used, so set it to `None`.
-                            last_comment = None;
+                    return Err(ParseError::UnexpectedComment(line_index + 1, file_name));
+                }
+            }
+            // If a line isn't a comment, it must hold a script name and script command.
+            else {
+                // Try to split the line.
+                match line.split_once(": ") {
+                    // If the splition is succesfull, continue.
+                    Some((key, value)) => {
+                        // Remove leading and trailing whitespaces.
+                        let name = key.trim();
+                        let command = value.trim();
 
-                            // Try to add name and script into database.
-                            match db.add(name, script) {
-                                Ok(()) => (),
-                                Err(err) => {
-                                    return Err(err.into_parse_error(line_index + 1, RUN_YAML))
-                                }
-                            }
+                        // The settings can only be written as a block.
+                        if name == SETTINGS {
+                            return Err(ParseError::BadValue(line_index + 1, file_name));
                         }
-                        // If the splition is failed, return an error.
-                        None => {
-                            return {
-                                if line.ends_with(":") {
-                                    Err(ParseError::NoCommand(line_index + 1, RUN_YAML))
-                                } else {
-                                    Err(ParseError::NoColon(line_index + 1))
-                                }
-                            }
+
+                        // Create a new `Script`.
+                        let script = match last_comment {
+                            Some(comment) => Script::new_with_comment(command, comment),
+                            None => Script::new(command),
+                        };
+
+                        // It has been used, so set it to `None`.
+                        last_comment = None;
+
+                        // Try to add name and script into database.
+                        match db.add(name, script) {
+                            Ok(()) => (),
+                            Err(err) => return Err(err.into_parse_error(line_index + 1, file_name)),
                         }
                     }
+                    // If a line only holds a name, the command and the options of the script are in the following lines.
+                    None if line.ends_with(':') => {
+                        // Remove the colon and trailing whitespaces.
+                        let name = line[..line.len() - 1].trim();
+
+                        // Start reading the settings, their comment isn't kept.
+                        if name == SETTINGS {
+                            last_comment = None;
+                            block = Some((Block::Settings, indentation));
+                            continue;
+                        }
+
+                        // Create a new `Script` without a command.
+                        let script = match last_comment {
+                            Some(comment) => Script::new_with_comment("", comment),
+                            None => Script::new(""),
+                        };
+
+                        // It has been used, so set it to `None`.
+                        last_comment = None;
+
+                        // Start reading the options of the script.
+                        block = Some((
+                            Block::Script(name, Box::new(script), line_index + 1),
+                            indentation,
+                        ));
+                    }
+                    // If the splition is failed, return an error.
+                    None => return Err(ParseError::NoColon(line_index + 1, file_name)),
                 }
             }
         }
 
-        // If current run.yaml content is bad, format it and save.
-        db.save_if_bad(run_yaml);
+        // Try to add the script being read at the end of run.yaml into database.
+        if let Some((Block::Script(name, script, line_no), _)) = block.take() {
+            if let Err(err) = db.add(name, *script) {
+                return Err(err.into_parse_error(line_no, file_name));
+            }
+        }
 
         // Successfully return the database.
         Ok(db)
     }
 }
 
+/// Sets the option of `script` with the given key to the given value.
+fn set_option<'a>(
+    script: &mut Script<'a>,
+    key: &'a str,
+    value: &'a str,
+) -> Result<(), FormatError> {
+    // Remove the quotes around the value, if any.
+    let value = unquote(value);
+
+    // Options must have a value.
+    if value.is_empty() {
+        return match key {
+            "command" => Err(FormatError::NoCommand),
+            _ => Err(FormatError::BadValue),
+        };
+    }
+
+    match key {
+        "command" => script.set_command(value),
+        "confirm" => script.set_confirm(value),
+        "exclusive" if value == "false" => (),
+        "exclusive" => script.set_exclusive(value),
+        "service" => script.set_service(parse_bool(&value)?),
+        "depends" => script.set_depends(value),
+        "parallel" => script.set_parallel(parse_bool(&value)?),
+        // There is no output to look at before executing the script.
+        "wait_for" => match Condition::parse(&value) {
+            Some(Condition::Output(_)) | None => return Err(FormatError::BadCondition),
+            Some(_) => script.set_wait_for(value),
+        },
+        "ready_when" => match Condition::parse(&value) {
+            Some(_) => script.set_ready_when(value),
+            None => return Err(FormatError::BadCondition),
+        },
+        "timeout" => match ready::parse_duration(&value) {
+            Some(_) => script.set_timeout(value),
+            None => return Err(FormatError::BadValue),
+        },
+        "log" => script.set_log(parse_bool(&value)?),
+        _ => return Err(FormatError::UnknownOption),
+    }
+
+    Ok(())
+}
+
+/// Returns the options of `script` as keys and values, to be saved to run.yaml later.
+///
+/// The command isn't included.
+pub(in crate::database) fn options_of(script: &Script) -> Vec<(&'static str, String)> {
+    let mut options = vec![];
+
+    if let Some(confirm) = script.confirm() {
+        options.push(("confirm", quote(confirm)));
+    }
+
+    if let Some(exclusive) = script.exclusive() {
+        options.push(("exclusive", exclusive.to_string()));
+    }
+
+    if script.service() {
+        options.push(("service", "true".to_string()));
+    }
+
+    let depends: Vec<&str> = script.depends().collect();
+
+    if !depends.is_empty() {
+        options.push(("depends", depends.join(", ")));
+    }
+
+    if script.parallel() {
+        options.push(("parallel", "true".to_string()));
+    }
+
+    if let Some(wait_for) = script.wait_for() {
+        options.push(("wait_for", quote(wait_for)));
+    }
+
+    if let Some(ready_when) = script.ready_when() {
+        options.push(("ready_when", quote(ready_when)));
+    }
+
+    if let Some(timeout) = script.timeout() {
+        options.push(("timeout", timeout.to_string()));
+    }
+
+    if let Some(log) = script.log() {
+        options.push(("log", log.to_string()));
+    }
+
+    options
+}
+
+/// Sets the setting with the given key to the given value.
+fn set_setting(settings: &mut Settings, key: &str, value: &str) -> Result<(), FormatError> {
+    match key {
+        "log" => settings.set_log(parse_bool(value)?),
+        "log_timestamps" => settings.set_log_timestamps(parse_bool(value)?),
+        "log_keep" => match value.parse() {
+            Ok(log_keep) if log_keep > 0 => settings.set_log_keep(log_keep),
+            _ => return Err(FormatError::BadValue),
+        },
+        "stats" => settings.set_stats(parse_bool(value)?),
+        "secrets" => settings.set_secrets(value),
+        "npm_compat" => settings.set_npm_compat(parse_bool(value)?),
+        "path" => settings.set_path(value),
+        "notify_after" => match value {
+            "false" => settings.set_notify_after(value),
+            _ => match ready::parse_duration(value) {
+                Some(_) => settings.set_notify_after(value),
+                None => return Err(FormatError::BadValue),
+            },
+        },
+        _ => return Err(FormatError::UnknownOption),
+    }
+
+    Ok(())
+}
+
+/// Returns the settings as keys and values, to be saved to run.yaml later.
+///
+/// The settings with default values aren't included.
+pub(in crate::database) fn settings_of(settings: &Settings) -> Vec<(&'static str, String)> {
+    let mut options = vec![];
+
+    if settings.log() {
+        options.push(("log", "true".to_string()));
+    }
+
+    if settings.log_timestamps() {
+        options.push(("log_timestamps", "true".to_string()));
+    }
+
+    if let Some(log_keep) = settings.log_keep() {
+        options.push(("log_keep", log_keep.to_string()));
+    }
+
+    if settings.stats() {
+        options.push(("stats", "true".to_string()));
+    }
+
+    if let Some(notify_after) = settings.notify_after() {
+        options.push(("notify_after", notify_after.to_string()));
+    }
+
+    let secrets: Vec<&str> = settings.secrets().collect();
+
+    if !secrets.is_empty() {
+        options.push(("secrets", secrets.join(", ")));
+    }
+
+    if settings.npm_compat() {
+        options.push(("npm_compat", "true".to_string()));
+    }
+
+    let path: Vec<&str> = settings.path().collect();
+
+    if !path.is_empty() {
+        options.push(("path", path.join(", ")));
+    }
+
+    options
+}
+
+/// Parses `true` or `false`.
+fn parse_bool(value: &str) -> Result<bool, FormatError> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(FormatError::BadValue),
+    }
+}
+
+/// Removes the matching quotes around `value`, if the whole value is a single quoted string.
+///
+/// In double quotes, `\"` and `\\` are escapes for `"` and `\`, and the other backslashes are kept, like `\d` of a regex.
+/// Values like `"echo" "hi"` are kept as they are, since their quotes belong to the shell.
+fn unquote(value: &str) -> Cow<'_, str> {
+    if let Some(unquoted) = value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+    {
+        if !unquoted.contains('\'') {
+            return Cow::Borrowed(unquoted);
+        }
+    }
+
+    if let Some(unescaped) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .and_then(unescape)
+    {
+        return unescaped;
+    }
+
+    Cow::Borrowed(value)
+}
+
+/// Returns the content of a double quoted string, or `None` if it has a quote which isn't escaped.
+fn unescape(quoted: &str) -> Option<Cow<'_, str>> {
+    if !quoted.contains('\\') {
+        return (!quoted.contains('"')).then_some(Cow::Borrowed(quoted));
+    }
+
+    let mut unescaped = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => return None,
+            '\\' => match chars.next() {
+                Some(escaped @ ('"' | '\\')) => unescaped.push(escaped),
+                Some(other) => {
+                    unescaped.push('\\');
+                    unescaped.push(other);
+                }
+                // The backslash escapes the closing quote, so the string isn't closed.
+                None => return None,
+            },
+            _ => unescaped.push(ch),
+        }
+    }
+
+    Some(Cow::Owned(unescaped))
+}
+
+/// Puts `value` in double quotes, escaping the quotes and the backslashes which would be read as escapes.
+fn quote(value: &str) -> String {
+    let mut quoted = String::from('"');
+    let mut chars = value.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' if matches!(chars.peek(), Some('"' | '\\') | None) => quoted.push_str("\\\\"),
+            _ => quoted.push(ch),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Returns the command of `script` as it's saved to run.yaml, which is quoted if it would be read differently otherwise.
+pub(in crate::database) fn saved_command<'s>(script: &'s Script) -> Cow<'s, str> {
+    match unquote(script.command()) == script.command() {
+        true => Cow::Borrowed(script.command()),
+        false => Cow::Owned(quote(script.command())),
+    }
+}
+
 #[test]
 fn test() {
     // Create a test content.
@@ -94,7 +462,7 @@ fn test() {
         "#;
 
     // Generate a database from run.yaml content.
-    let db = Database::from_run_yaml(run_yaml).ok().unwrap();
+    let db = Database::parse_run_yaml(run_yaml).ok().unwrap();
 
     // Get all the scripts and names from the database.
     let scripts_and_names = db.scripts_and_names();
@@ -103,14 +471,14 @@ fn test() {
     assert_eq!(scripts_and_names.len(), 2);
 
     // Get first script.
-    let (compile_name, compile_script) = scripts_and_names.get(0).unwrap();
+    let (compile_name, compile_script) = scripts_and_names.first().unwrap();
 
     // Get second script.
     let (greet_name, greet_script) = scripts_and_names.get(1).unwrap();
 
     // Check names.
-    assert_eq!(compile_name, &&"compile");
-    assert_eq!(greet_name, &&"greet");
+    assert_eq!(compile_name, &"compile");
+    assert_eq!(greet_name, &"greet");
 
     // Check commands.
     assert_eq!(compile_script.command(), "tsc");
@@ -120,3 +488,97 @@ fn test() {
     assert_eq!(compile_script.comment(), "Compiles the project.");
     assert_eq!(greet_script.comment(), "Prints a greeting message.");
 }
+
+#[test]
+fn test_options() {
+    // Create a test content.
+    let run_yaml = r#"
+        # Drops the local database.
+        reset:
+            command: dropdb app
+            confirm: "This will drop the local database. Continue?"
+
+        # Prints a greeting message.
+        greet: echo hey!
+        "#;
+
+    // Generate a database from run.yaml content.
+    let db = Database::parse_run_yaml(run_yaml).ok().unwrap();
+
+    // Get all the scripts and names from the database.
+    let scripts_and_names = db.scripts_and_names();
+
+    // There must be 2 scripts.
+    assert_eq!(scripts_and_names.len(), 2);
+
+    // Get second script.
+    let (reset_name, reset_script) = scripts_and_names.get(1).unwrap();
+
+    // Check the script with options.
+    assert_eq!(reset_name, &"reset");
+    assert_eq!(reset_script.command(), "dropdb app");
+    assert_eq!(reset_script.comment(), "Drops the local database.");
+    assert_eq!(
+        reset_script.confirm(),
+        Some("This will drop the local database. Continue?")
+    );
+
+    // The extraction must be parsed into the same database.
+    let extraction = db.extract();
+    let db = Database::parse_run_yaml(&extraction).ok().unwrap();
+    assert_eq!(db.extract(), extraction);
+
+    // Unknown options must be rejected.
+    assert!(Database::parse_run_yaml("reset:\n  command: dropdb app\n  what: ever").is_err());
+
+    // Scripts without a command must be rejected, unless they have dependencies.
+    assert!(Database::parse_run_yaml("reset:\n  confirm: Sure?").is_err());
+
+    let db = Database::parse_run_yaml("all:\n  depends: a, b\n  parallel: true")
+        .ok()
+        .unwrap();
+    let (_, all_script) = db.scripts_and_names().remove(0);
+
+    assert_eq!(all_script.command(), "");
+    assert!(all_script.parallel());
+
+    // Only the quotes around a single quoted string must be removed.
+    let command_of = |run_yaml: &'static str| {
+        let db = Database::parse_run_yaml(run_yaml).ok().unwrap();
+        let (_, script) = db.scripts_and_names().remove(0);
+        script.command().to_string()
+    };
+
+    assert_eq!(command_of("a:\n  command: \"echo hi\""), "echo hi");
+    assert_eq!(command_of("a:\n  command: 'echo \"hi\"'"), "echo \"hi\"");
+    assert_eq!(
+        command_of("a:\n  command: \"echo\" \"hi\" \"there\""),
+        "\"echo\" \"hi\" \"there\""
+    );
+    assert_eq!(command_of("a:\n  command: 'a' 'b'"), "'a' 'b'");
+
+    // The escaped quotes and backslashes must be read, and the other backslashes must be kept.
+    assert_eq!(
+        command_of("a:\n  command: \"echo \\\"hi\\\"\""),
+        "echo \"hi\""
+    );
+    assert_eq!(command_of("a:\n  command: \"grep \\d\""), "grep \\d");
+
+    // The quotes and the backslashes must survive being saved and read again.
+    let mut script = Script::new("\"echo hi\"");
+
+    script.set_confirm("Drop \"main\" from C:\\data\\?");
+    script.set_depends("b");
+
+    let mut db = Database::parse_run_yaml("b: true").ok().unwrap();
+
+    db.add("a", script).ok().unwrap();
+
+    let extraction = db.extract();
+    let db = Database::parse_run_yaml(&extraction).ok().unwrap();
+    let (_, script) = db.scripts_and_names().remove(0);
+
+    assert_eq!(script.command(), "\"echo hi\"");
+    assert_eq!(script.confirm(), Some("Drop \"main\" from C:\\data\\?"));
+    assert_eq!(db.extract(), extraction);
+}
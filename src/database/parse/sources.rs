@@ -0,0 +1,169 @@
+use crate::{
+    database::{db::Database, errors::ParseError},
+    events,
+    file::{self, FileError},
+    json,
+};
+
+use super::{DENO_JSON, DENO_JSONC, PACKAGE_JSON, RUN_YAML};
+
+/// The manifests the scripts are read from when there is no run.yaml, with the prefixes of their scripts.
+///
+/// Only the first one of the manifests with the same prefix is read, as Deno does for its configuration files.
+const MANIFESTS: [(&str, &str); 3] = [
+    (PACKAGE_JSON, "npm"),
+    (DENO_JSON, "deno"),
+    (DENO_JSONC, "deno"),
+];
+
+/// Reads run.yaml, or the manifests in the current directory if it doesn't exist, then returns their names and contents.
+pub fn read_sources() -> Result<Vec<(&'static str, String)>, FileError> {
+    if file::exists(RUN_YAML) {
+        return Ok(vec![(RUN_YAML, file::read(RUN_YAML)?)]);
+    }
+
+    let sources = read_manifests()?;
+
+    match sources.is_empty() {
+        true => Err(FileError::NotFound(RUN_YAML)),
+        false => Ok(sources),
+    }
+}
+
+/// Reads the manifests in the current directory, then returns their names and contents.
+pub fn read_manifests() -> Result<Vec<(&'static str, String)>, FileError> {
+    let mut manifests = vec![];
+    let mut prefixes = vec![];
+
+    for (file_name, prefix) in MANIFESTS {
+        if file::exists(file_name) && !prefixes.contains(&prefix) {
+            manifests.push((file_name, file::read(file_name)?));
+            prefixes.push(prefix);
+        }
+    }
+
+    Ok(manifests)
+}
+
+/// Returns the prefix of the scripts read from the manifest, like `npm` for package.json.
+pub(in crate::database) fn prefix_of(file_name: &'static str) -> &'static str {
+    MANIFESTS
+        .iter()
+        .find(|(manifest, _)| *manifest == file_name)
+        .map(|(_, prefix)| *prefix)
+        .unwrap_or(file_name)
+}
+
+impl<'a> Database<'a> {
+    /// Creates a database from the sources returned by `read_sources`.
+    ///
+    /// If there are several manifests, their scripts are prefixed with their sources, like `npm:build` and `deno:start`.
+    pub fn from_sources(sources: &'a [(&'static str, String)]) -> Result<Database<'a>, ParseError> {
+        if let [(RUN_YAML, run_yaml)] = sources {
+            return Database::from_run_yaml(run_yaml);
+        }
+
+        let db = match sources {
+            [(file_name, content)] => Database::from_manifest(file_name, content)?,
+            _ => {
+                let mut db = Database::new();
+
+                for (file_name, content) in sources {
+                    let prefix = prefix_of(file_name);
+
+                    let manifest_db = Database::from_manifest(file_name, content)?;
+
+                    db.add_source(prefix, file_name, manifest_db)
+                        .map_err(|err| err.into_parse_error(1, *file_name))?;
+                }
+
+                db
+            }
+        };
+
+        events::emit(
+            "database_loaded",
+            &[
+                (
+                    "file",
+                    json::string(
+                        &sources
+                            .iter()
+                            .map(|(file_name, _)| *file_name)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                ),
+                ("scripts", db.scripts().count().to_string()),
+            ],
+        );
+
+        Ok(db)
+    }
+
+    /// Creates a database from the manifest with the given name.
+    pub(in crate::database) fn from_manifest(
+        file_name: &str,
+        content: &'a str,
+    ) -> Result<Database<'a>, ParseError> {
+        match file_name {
+            PACKAGE_JSON => Database::from_package_json(content),
+            DENO_JSON => Database::from_deno_json(content),
+            _ => Database::from_deno_jsonc(content),
+        }
+    }
+}
+
+#[test]
+fn test() {
+    use crate::database::errors::DatabaseError;
+
+    let sources = [
+        (
+            PACKAGE_JSON,
+            r#"{ "scripts": { "build": "tsc", "ci": "npm run build" } }"#.to_string(),
+        ),
+        (
+            DENO_JSONC,
+            r#"{ "tasks": { "start": "deno run main.ts" } }"#.to_string(),
+        ),
+    ];
+
+    let db = Database::from_sources(&sources).ok().unwrap();
+
+    let names: Vec<_> = db.scripts().map(|(name, _)| name).collect();
+
+    // The scripts must be prefixed with their sources.
+    assert_eq!(names, ["deno:start", "npm:build", "npm:ci"]);
+
+    // The dependencies must also be prefixed.
+    let (_, ci_script) = db.get("npm:ci").ok().unwrap();
+
+    assert_eq!(ci_script.depends().collect::<Vec<_>>(), ["npm:build"]);
+
+    // The scripts must be found without their prefixes.
+    assert!(matches!(db.get("start"), Ok(("deno:start", _))));
+
+    // The aliases must be made from the names without their prefixes.
+    assert!(matches!(db.get("s"), Ok(("deno:start", _))));
+    assert!(matches!(db.get("c"), Ok(("npm:ci", _))));
+    assert!(matches!(db.get("n"), Err(DatabaseError::NoAlias('n'))));
+
+    // The aliases shared by the sources must be rejected.
+    let sources = [
+        sources[0].clone(),
+        (
+            DENO_JSONC,
+            r#"{ "tasks": { "bundle": "deno bundle" } }"#.to_string(),
+        ),
+    ];
+
+    let db = Database::from_sources(&sources).ok().unwrap();
+
+    assert!(matches!(db.get("b"), Err(DatabaseError::MultiAlias('b'))));
+
+    // A single manifest must be used without prefixes.
+    let db = Database::from_sources(&sources[..1]).ok().unwrap();
+
+    assert!(db.contains("build"));
+}
@@ -0,0 +1,212 @@
+use std::{
+    borrow::Cow,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// The built-in templates of run.yaml, with the files of the projects they are used for.
+const TEMPLATES: [(&str, &str, &str); 6] = [
+    ("rust", "Cargo.toml", RUST),
+    ("go", "go.mod", GO),
+    ("python", "pyproject.toml", PYTHON),
+    ("ruby", "Gemfile", RUBY),
+    ("cmake", "CMakeLists.txt", CMAKE),
+    ("make", "Makefile", MAKE),
+];
+
+const RUST: &str = "# Builds the project.
+build: cargo build
+
+# Removes the build artifacts.
+clean: cargo clean
+
+# Formats the code.
+fmt: cargo fmt
+
+# Lints the code.
+lint: cargo clippy --all-targets -- -D warnings
+
+# Runs the project.
+run: cargo run
+
+# Runs the tests.
+test: cargo test
+";
+
+const GO: &str = "# Builds the project.
+build: go build ./...
+
+# Removes the build artifacts.
+clean: go clean
+
+# Formats the code.
+fmt: go fmt ./...
+
+# Lints the code.
+lint: go vet ./...
+
+# Runs the project.
+run: go run .
+
+# Runs the tests.
+test: go test ./...
+";
+
+const PYTHON: &str = "# Builds the package.
+build: python -m build
+
+# Removes the build artifacts.
+clean: rm -rf build dist .pytest_cache
+
+# Formats the code.
+fmt: ruff format .
+
+# Lints the code.
+lint: ruff check .
+
+# Runs the project.
+run: python main.py
+
+# Runs the tests.
+test: python -m pytest
+";
+
+const RUBY: &str = "# Installs the dependencies.
+build: bundle install
+
+# Removes the build artifacts.
+clean: rm -rf pkg tmp
+
+# Formats the code.
+fmt: bundle exec rubocop --autocorrect
+
+# Lints the code.
+lint: bundle exec rubocop
+
+# Runs the project.
+run: bundle exec ruby main.rb
+
+# Runs the tests.
+test: bundle exec rake test
+";
+
+const CMAKE: &str = "# Builds the project.
+build: cmake -S . -B build && cmake --build build
+
+# Removes the build artifacts.
+clean: rm -rf build
+
+# Formats the code.
+fmt: clang-format -i src/*.cpp src/*.h
+
+# Lints the code.
+lint:
+  command: clang-tidy -p build src/*.cpp
+  depends: build
+
+# Runs the tests.
+test:
+  command: ctest --test-dir build
+  depends: build
+";
+
+const MAKE: &str = "# Builds the project.
+build: make
+
+# Removes the build artifacts.
+clean: make clean
+
+# Runs the project.
+run: make run
+
+# Runs the tests.
+test: make test
+";
+
+/// Returns the name of the built-in template for the project in the current directory, if there is any.
+pub fn detect_template() -> Option<&'static str> {
+    TEMPLATES
+        .iter()
+        .find(|(_, file_name, _)| Path::new(file_name).is_file())
+        .map(|(name, _, _)| *name)
+}
+
+/// Returns the name its errors are reported with and the content of the template with the given name.
+///
+/// The templates of the user, like `~/.config/run/templates/rust.yaml`, come before the built-in ones,
+/// and they are reported with their paths. The names with a path, like `../rust`, aren't templates.
+pub fn read_template(name: &str) -> Option<(String, Cow<'static, str>)> {
+    if name.contains(['/', '\\']) || name.contains("..") {
+        return None;
+    }
+
+    let user_template = user_templates_dir()
+        .map(|dir| dir.join(format!("{}.yaml", name)))
+        .and_then(|path| Some((path.display().to_string(), fs::read_to_string(path).ok()?)));
+
+    match user_template {
+        Some((path, template)) => Some((path, Cow::Owned(template))),
+        None => TEMPLATES
+            .iter()
+            .find(|(template_name, _, _)| *template_name == name)
+            .map(|(_, _, template)| (format!("the {} template", name), Cow::Borrowed(*template))),
+    }
+}
+
+/// Returns the names of the templates, including the ones of the user.
+pub fn template_names() -> Vec<String> {
+    let mut names: Vec<String> = TEMPLATES
+        .iter()
+        .map(|(name, _, _)| name.to_string())
+        .collect();
+
+    let user_names = user_templates_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            file_name.strip_suffix(".yaml").map(str::to_string)
+        });
+
+    for name in user_names {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+/// Returns the directory of the templates of the user, which is `run/templates` in the config directory.
+fn user_templates_dir() -> Option<PathBuf> {
+    let config_dir = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    config_dir.map(|dir| dir.join("run").join("templates"))
+}
+
+#[test]
+fn test() {
+    use crate::database::Database;
+
+    // The built-in templates must be valid.
+    for (name, _, template) in TEMPLATES {
+        let db = Database::parse_run_yaml(template).ok().unwrap();
+
+        assert!(db.contains("build"), "{} has no build script", name);
+        assert!(db.contains("test"), "{} has no test script", name);
+    }
+
+    // The names with a path mustn't be read outside of the templates.
+    assert!(read_template("../rust").is_none());
+    assert!(read_template("a/b").is_none());
+    assert!(read_template("a\\b").is_none());
+}
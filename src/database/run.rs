@@ -0,0 +1,955 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    args::Args,
+    beautify::Beautify,
+    ci, date,
+    events::{self, Events},
+    history::{self, Entry},
+    json,
+    lock::{self, Group, Lock},
+    log::{self, Log},
+    notify::{self, DEFAULT_NOTIFY_AFTER},
+    npm,
+    output::Sink,
+    progress::Progress,
+    prompt,
+    ready::{self, DEFAULT_TIMEOUT},
+    report::{self, Tail},
+    script::{self, Script},
+    secrets::Masker,
+    service::{self, Status},
+    trace,
+};
+
+use super::{db::Database, errors::DatabaseError};
+
+/// The ratio of a run's duration to the typical duration, for the run to be considered slow.
+const SLOWDOWN_RATIO: f64 = 1.5;
+
+/// The state shared by a script and its dependencies while they are run.
+#[derive(Clone, Default)]
+struct Context<'a> {
+    /// The names of the scripts whose dependencies are being run, to detect circular dependencies.
+    chain: Vec<&'a str>,
+    /// The names of the dependencies which are already run.
+    done: HashSet<&'a str>,
+    /// The names of the services started for the dependencies, to be stopped at the end.
+    started: Vec<&'a str>,
+    /// Whether the script is run at the same time as others, so its output can't be grouped in CI.
+    parallel: bool,
+    /// Whether the confirmations can be asked.
+    interactive: bool,
+}
+
+impl<'a> Database<'a> {
+    /// Executes the associated script after its dependencies, and then returns it's exit code.
+    ///
+    /// If the script asks for a confirmation, it's asked before executing, unless `--yes` is given.
+    ///
+    /// If the script is exclusive, its lock is held while executing.
+    ///
+    /// The services among the dependencies are started in background, and stopped at the end if they are started by this run.
+    ///
+    /// The script arguments are appended to the command of the script, but not to its dependencies.
+    pub fn run(
+        &'a self,
+        alias_or_name: &'a str,
+        script_args: &[String],
+        args: &Args,
+    ) -> Result<i32, DatabaseError<'a>> {
+        let (name, script) = self.get(alias_or_name)?;
+
+        events::emit(
+            "script_resolved",
+            &[
+                ("alias", json::string(alias_or_name)),
+                ("name", json::string(name)),
+            ],
+        );
+
+        report::set_name(name);
+
+        let start_time = Instant::now();
+
+        let mut context = Context {
+            interactive: prompt::is_interactive(),
+            ..Context::default()
+        };
+
+        let result = self.run_script(name, script, script_args, args, &mut context);
+
+        // Stop the services started for the dependencies, in reverse order.
+        if !context.started.is_empty() {
+            println!();
+        }
+
+        for name in context.started.into_iter().rev() {
+            match service::stop(name) {
+                Ok(_) => {
+                    println!("{} {}", "down".green(), name.yellow());
+                    events::emit("service_stopped", &[("name", json::string(name))]);
+                }
+                Err(err) => eprintln!("{}", err),
+            }
+        }
+
+        // Let the user know the script is finished, if it took long.
+        let elapsed = start_time.elapsed();
+
+        let status = match result {
+            Ok(0) => "done".to_string(),
+            Ok(exit_code) => format!("failed with error code {}", exit_code),
+            Err(_) => "failed".to_string(),
+        };
+
+        // The title is only changed while the script is running.
+        notify::restore_title();
+
+        if self
+            .notify_after()
+            .is_some_and(|notify_after| elapsed >= notify_after)
+        {
+            notify::notify(name, &format!("{} in {:.2?}", status, elapsed));
+        }
+
+        trace::span(
+            &format!("run {}", name),
+            "run",
+            start_time,
+            &[(
+                "exit_code",
+                result.as_ref().map_or("null".to_string(), i32::to_string),
+            )],
+        );
+
+        if let Ok(exit_code) = result {
+            events::emit(
+                "run_finished",
+                &[
+                    ("name", json::string(name)),
+                    ("exit_code", exit_code.to_string()),
+                    ("duration_ms", start_time.elapsed().as_millis().to_string()),
+                ],
+            );
+        }
+
+        result
+    }
+
+    /// Executes the script after its dependencies, and then returns it's exit code.
+    ///
+    /// In npm compatibility mode, the `pre` and `post` scripts of the script are also executed before and after it, if they exist.
+    fn run_script(
+        &'a self,
+        name: &'a str,
+        script: &'a Script<'a>,
+        script_args: &[String],
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        if !self.settings().npm_compat() {
+            return self.execute_script(name, script, script_args, args, context);
+        }
+
+        if let Some((pre_name, pre_script)) = self.hook("pre", name) {
+            let exit_code = self.run_hook(name, pre_name, pre_script, args, context)?;
+
+            // Don't execute the script, if its `pre` script fails.
+            if exit_code != 0 {
+                return Ok(exit_code);
+            }
+
+            println!();
+        }
+
+        let exit_code = self.execute_script(name, script, script_args, args, context)?;
+
+        match self.hook("post", name) {
+            Some((post_name, post_script)) if exit_code == 0 => {
+                println!();
+
+                self.run_hook(name, post_name, post_script, args, context)
+            }
+            _ => Ok(exit_code),
+        }
+    }
+
+    /// Returns the script with the name having the given prefix, like `prebuild` for `build`.
+    fn hook(&'a self, prefix: &str, name: &str) -> Option<(&'a str, &'a Script<'a>)> {
+        // The prefix of the source comes first, like `npm:prebuild` for `npm:build`.
+        let hook_name = match self.split_source(name) {
+            (Some(source), name) => format!("{}:{}{}", source, prefix, name),
+            (None, name) => format!("{}{}", prefix, name),
+        };
+
+        self.scripts()
+            .find(|(script_name, _)| *script_name == hook_name)
+    }
+
+    /// Executes the `pre` or `post` script of the script, like a dependency of it, and then returns its exit code.
+    fn run_hook(
+        &'a self,
+        name: &'a str,
+        hook_name: &'a str,
+        hook: &'a Script<'a>,
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        context.chain.push(name);
+
+        let exit_code = self.execute_script(hook_name, hook, &[], args, context)?;
+
+        context.chain.pop();
+
+        Ok(exit_code)
+    }
+
+    /// Executes the script after its dependencies, without its `pre` and `post` scripts, and then returns it's exit code.
+    fn execute_script(
+        &'a self,
+        name: &'a str,
+        script: &'a Script<'a>,
+        script_args: &[String],
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        // The script is run directly, if it's not a dependency of another script.
+        let direct = context.chain.is_empty();
+
+        // The arguments would be dropped, as they aren't appended to the dependencies.
+        if script.command().is_empty() && !script_args.is_empty() {
+            return Err(DatabaseError::NoArgsCommand(name));
+        }
+
+        // The confirmation is asked before anything is run for the script, and the lock is held until it's finished.
+        let _lock = self.confirm_and_lock(name, script, args, context.interactive)?;
+
+        let start = date::unix_millis();
+        let start_time = Instant::now();
+
+        let exit_code = self.run_dependencies(name, script, args, context)?;
+
+        // Don't execute the script, if a dependency fails.
+        if exit_code != 0 {
+            // A script without a command is recorded like the others, as running it means running its dependencies.
+            if script.command().is_empty() {
+                let duration = start_time.elapsed();
+
+                record(
+                    name,
+                    script,
+                    script_args,
+                    direct,
+                    start,
+                    duration,
+                    exit_code,
+                );
+            }
+
+            return Ok(exit_code);
+        }
+
+        // Wait for the condition of the script, if any.
+        if let Some(condition) = script.wait_for() {
+            println!("{} {}\n", "waiting for".yellow(), condition.green());
+
+            events::emit(
+                "waiting",
+                &[
+                    ("name", json::string(name)),
+                    ("condition", json::string(condition)),
+                ],
+            );
+
+            let start_time = Instant::now();
+
+            let result = ready::wait(condition, None, timeout_of(script));
+
+            trace::span(
+                &format!("waiting for {}", condition),
+                "wait",
+                start_time,
+                &[],
+            );
+
+            result.map_err(DatabaseError::NotReady)?;
+        }
+
+        // A script without a command only runs its dependencies.
+        if script.command().is_empty() {
+            record(
+                name,
+                script,
+                script_args,
+                direct,
+                start,
+                start_time.elapsed(),
+                0,
+            );
+
+            return Ok(0);
+        }
+
+        // The typical duration of the script is used to show its progress, and to notice slowdowns.
+        let typical = history::typical(&history::read(), name);
+
+        // In CI, the output of the script is put in a collapsible group.
+        let title = format!("run {}", name);
+
+        if !context.parallel {
+            ci::start_group(name, &title);
+        }
+
+        notify::set_title(&title);
+
+        match typical {
+            Some(typical) => println!(
+                "{} {} {}\n",
+                "run".green(),
+                name.yellow(),
+                format!("(usually {:.2?})", typical).yellow()
+            ),
+            None => println!("{} {}\n", "run".green(), name.yellow()),
+        }
+
+        // Write the output to a log file too, if logging is enabled for the script.
+        let mut log = match script.log().unwrap_or(self.settings().log()) {
+            true => self.create_log(name, script),
+            false => None,
+        };
+
+        let mut sinks: Vec<&mut dyn Sink> = vec![];
+
+        if let Some(log) = &mut log {
+            sinks.push(log);
+        }
+
+        // Emit the output as events too, if the events are enabled.
+        let mut events_sink = Events::new(name);
+
+        if events::enabled() {
+            sinks.push(&mut events_sink);
+        }
+
+        // Keep the last lines of stderr for the report, if it's asked for.
+        let mut tail = Tail::default();
+
+        if report::enabled() {
+            sinks.push(&mut tail);
+        }
+
+        events::emit(
+            "script_started",
+            &[
+                ("name", json::string(name)),
+                (
+                    "command",
+                    json::string(&script::with_args(script.command(), script_args)),
+                ),
+                ("dependency", (!direct).to_string()),
+            ],
+        );
+
+        // Mask the values of the secrets in the output, if any of them is set.
+        let masker = Masker::new(self.settings().secrets());
+
+        // The line of the progress is only shown if the output goes through run, so the script keeps the terminal otherwise.
+        let captured = !sinks.is_empty() || masker.is_some();
+        let progress = typical.and_then(|typical| Progress::start(name, typical, captured));
+
+        let start = date::unix_millis();
+        let start_time = Instant::now();
+
+        // In npm compatibility mode, the script gets the environment variables npm sets.
+        let env = match self.settings().npm_compat() {
+            true => npm::env(self.split_source(name).1, script.command()),
+            false => vec![],
+        };
+
+        let (exit_code, usage) = script.execute(
+            &self.extra_paths(),
+            &env,
+            script_args,
+            masker.as_ref(),
+            &mut sinks,
+        );
+
+        let end_time = start_time.elapsed();
+
+        drop(progress);
+
+        if let Some(log) = &mut log {
+            log.finish(exit_code, end_time);
+        }
+
+        report::case(name, end_time, exit_code, &tail);
+
+        trace::span(
+            name,
+            "script",
+            start_time,
+            &[
+                (
+                    "command",
+                    json::string(&script::with_args(script.command(), script_args)),
+                ),
+                ("exit_code", exit_code.to_string()),
+            ],
+        );
+
+        events::emit(
+            "script_finished",
+            &[
+                ("name", json::string(name)),
+                ("exit_code", exit_code.to_string()),
+                ("duration_ms", end_time.as_millis().to_string()),
+            ],
+        );
+
+        record(
+            name,
+            script,
+            script_args,
+            direct,
+            start,
+            end_time,
+            exit_code,
+        );
+
+        // The result is put after the group, so it's visible while the group is collapsed.
+        if !context.parallel {
+            ci::end_group(name, &title);
+        }
+
+        match exit_code {
+            0 => println!(
+                "\n{} {}",
+                "in".green(),
+                format!("{:.2?}", end_time).yellow()
+            ),
+
+            2 => println!("\n{}", "permission denied".red()),
+
+            126 => println!(
+                "\n{} {}",
+                script
+                    .command()
+                    .split_once(" ")
+                    .unwrap_or((script.command(), ""))
+                    .0
+                    .yellow()
+                    .yellow(),
+                "can't be executed".red()
+            ),
+
+            127 => println!(
+                "\n{} {}",
+                script
+                    .command()
+                    .split_once(" ")
+                    .unwrap_or((script.command(), ""))
+                    .0
+                    .yellow()
+                    .yellow(),
+                "is not found".red()
+            ),
+
+            _ => println!("\n{} {}", "error code".red(), exit_code.green()),
+        }
+
+        if exit_code != 0 {
+            ci::error(&format!("{} failed with error code {}", name, exit_code));
+        }
+
+        // Display the resources used by the script, if they are asked for.
+        if let Some(usage) = usage.filter(|_| args.stats() || self.settings().stats()) {
+            println!("{}", usage.to_string().yellow());
+        }
+
+        // Warn if the script took significantly longer than usual.
+        if let Some(typical) =
+            typical.filter(|typical| exit_code == 0 && is_slow(end_time, *typical))
+        {
+            println!(
+                "{} {} {}{}",
+                name.yellow(),
+                "took".red(),
+                format!("{:.2?}", end_time).yellow(),
+                format!(", usually {:.2?}", typical).red()
+            );
+        }
+
+        Ok(exit_code)
+    }
+
+    /// Returns the time a script must take to notify when it's finished, if the notifications are enabled.
+    fn notify_after(&'a self) -> Option<Duration> {
+        match self.settings().notify_after() {
+            Some("false") => None,
+            Some(notify_after) => ready::parse_duration(notify_after),
+            None => Some(DEFAULT_NOTIFY_AFTER),
+        }
+    }
+
+    /// Asks for the confirmation of the script if it needs one, then takes its lock if it's exclusive.
+    ///
+    /// If `interactive` is `false`, the confirmation can't be asked, so it must be given with `--yes`.
+    pub(super) fn confirm_and_lock(
+        &'a self,
+        name: &'a str,
+        script: &'a Script<'a>,
+        args: &Args,
+        interactive: bool,
+    ) -> Result<Option<Lock>, DatabaseError<'a>> {
+        if let Some(question) = script.confirm() {
+            if !args.yes() {
+                // The confirmation can't be asked, if there is no one to answer it.
+                if !interactive {
+                    return Err(DatabaseError::NotConfirmed(name));
+                }
+
+                if !prompt::confirm(name, question) {
+                    return Err(DatabaseError::Cancelled(name));
+                }
+            }
+        }
+
+        match script.exclusive() {
+            Some("true") => Some(lock::acquire(Group::Script(name), args.wait())),
+            Some(group) => Some(lock::acquire(Group::Named(group), args.wait())),
+            None => None,
+        }
+        .transpose()
+        .map_err(DatabaseError::Locked)
+    }
+
+    /// Creates a log file for the script.
+    ///
+    /// If it fails, prints a warning, as the script can still be executed.
+    fn create_log(&'a self, name: &str, script: &Script) -> Option<Log> {
+        let keep = self.settings().log_keep().unwrap_or(log::DEFAULT_KEEP);
+        let timestamps = self.settings().log_timestamps();
+
+        match Log::create(name, script.command(), keep, timestamps) {
+            Ok(log) => Some(log),
+            Err(_) => {
+                eprintln!("{} {}\n", "can't write the log of".red(), name.green());
+                None
+            }
+        }
+    }
+
+    /// Runs the dependencies of the script, and then returns the first non-zero exit code, if any.
+    ///
+    /// The dependencies are run in order, or at the same time if the script is parallel.
+    fn run_dependencies(
+        &'a self,
+        name: &'a str,
+        script: &'a Script<'a>,
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        if context.chain.contains(&name) {
+            return Err(DatabaseError::CircularDependency(name));
+        }
+
+        context.chain.push(name);
+
+        let mut dependencies = vec![];
+
+        for alias_or_name in script.depends() {
+            dependencies.push(self.get(alias_or_name)?);
+        }
+
+        let exit_code = if script.parallel() && dependencies.len() > 1 {
+            // The dependencies shared by the parallel ones are run first, so they are run once, not by several threads.
+            let shared = self.shared_dependencies(&dependencies, &context.done)?;
+
+            let exit_code = self.run_sequential_dependencies(name, &shared, args, context)?;
+
+            let parallel: Vec<_> = dependencies
+                .into_iter()
+                .filter(|(dependency_name, _)| context.done.insert(dependency_name))
+                .collect();
+
+            match exit_code {
+                0 if parallel.len() > 1 => {
+                    self.run_parallel_dependencies(name, &parallel, args, context)?
+                }
+                0 => self.run_sequential_dependencies(name, &parallel, args, context)?,
+                _ => exit_code,
+            }
+        } else {
+            self.run_sequential_dependencies(name, &dependencies, args, context)?
+        };
+
+        if exit_code != 0 {
+            return Ok(exit_code);
+        }
+
+        context.chain.pop();
+
+        Ok(0)
+    }
+
+    /// Runs the dependencies in order, and then returns the first non-zero exit code, if any.
+    ///
+    /// A dependency is run once, even if several scripts depend on it.
+    fn run_sequential_dependencies(
+        &'a self,
+        name: &'a str,
+        dependencies: &[(&'a str, &'a Script<'a>)],
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        for &(dependency_name, dependency) in dependencies {
+            if !context.done.insert(dependency_name) {
+                continue;
+            }
+
+            let exit_code =
+                self.run_dependency(name, dependency_name, dependency, args, context)?;
+
+            if exit_code != 0 {
+                return Ok(exit_code);
+            }
+
+            println!();
+        }
+
+        Ok(0)
+    }
+
+    /// Returns the scripts which are needed by more than one of the given dependencies and aren't run yet,
+    /// each of them after its own dependencies.
+    ///
+    /// A dependency needed by another one of them is also included, so it's finished before the other one starts.
+    fn shared_dependencies(
+        &'a self,
+        dependencies: &[(&'a str, &'a Script<'a>)],
+        done: &HashSet<&'a str>,
+    ) -> Result<Vec<(&'a str, &'a Script<'a>)>, DatabaseError<'a>> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut order = vec![];
+
+        for &(dependency_name, dependency) in dependencies {
+            let mut needed = vec![];
+
+            self.collect_dependencies(dependency, &mut HashSet::new(), &mut needed)?;
+
+            needed.push((dependency_name, dependency));
+
+            for (needed_name, needed_script) in needed {
+                let count = counts.entry(needed_name).or_default();
+
+                if *count == 0 {
+                    order.push((needed_name, needed_script));
+                }
+
+                *count += 1;
+            }
+        }
+
+        Ok(order
+            .into_iter()
+            .filter(|(name, _)| counts[name] > 1 && !done.contains(name))
+            .collect())
+    }
+
+    /// Adds all the dependencies of the script to `needed`, each of them after its own dependencies.
+    ///
+    /// The circular dependencies are skipped, they are reported when they are run.
+    fn collect_dependencies(
+        &'a self,
+        script: &'a Script<'a>,
+        visited: &mut HashSet<&'a str>,
+        needed: &mut Vec<(&'a str, &'a Script<'a>)>,
+    ) -> Result<(), DatabaseError<'a>> {
+        for alias_or_name in script.depends() {
+            let (name, dependency) = self.get(alias_or_name)?;
+
+            if visited.insert(name) {
+                self.collect_dependencies(dependency, visited, needed)?;
+                needed.push((name, dependency));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the dependencies at the same time, and then returns the first non-zero exit code, if any.
+    ///
+    /// All of them are waited for, even if some of them fail.
+    fn run_parallel_dependencies(
+        &'a self,
+        name: &'a str,
+        dependencies: &[(&'a str, &'a Script<'a>)],
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = dependencies
+                .iter()
+                .map(|&(dependency_name, dependency)| {
+                    // Each dependency has its own context, which is merged at the end.
+                    let mut context = Context {
+                        parallel: true,
+                        ..context.clone()
+                    };
+
+                    scope.spawn(move || {
+                        let result = self.run_dependency(
+                            name,
+                            dependency_name,
+                            dependency,
+                            args,
+                            &mut context,
+                        );
+
+                        (result, context)
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("a dependency panicked"))
+                .collect()
+        });
+
+        let mut exit_code = Ok(0);
+
+        for (result, dependency_context) in results {
+            context.done.extend(dependency_context.done);
+
+            for started in dependency_context.started {
+                if !context.started.contains(&started) {
+                    context.started.push(started);
+                }
+            }
+
+            if let Ok(0) = exit_code {
+                exit_code = result;
+            }
+        }
+
+        if let Ok(0) = exit_code {
+            println!();
+        }
+
+        exit_code
+    }
+
+    /// Runs a dependency of the script, or starts it if it's a service, and then returns its exit code.
+    fn run_dependency(
+        &'a self,
+        name: &'a str,
+        dependency_name: &'a str,
+        dependency: &'a Script<'a>,
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        events::emit(
+            "dependency_started",
+            &[
+                ("name", json::string(dependency_name)),
+                ("of", json::string(name)),
+            ],
+        );
+
+        let exit_code = if dependency.service() {
+            self.start_dependency(dependency_name, dependency, args, context)?
+        } else {
+            self.run_script(dependency_name, dependency, &[], args, context)?
+        };
+
+        events::emit(
+            "dependency_finished",
+            &[
+                ("name", json::string(dependency_name)),
+                ("of", json::string(name)),
+                ("exit_code", exit_code.to_string()),
+            ],
+        );
+
+        Ok(exit_code)
+    }
+
+    /// Starts the service in background if it isn't running, then waits until it's ready.
+    fn start_dependency(
+        &'a self,
+        name: &'a str,
+        script: &'a Script<'a>,
+        args: &Args,
+        context: &mut Context<'a>,
+    ) -> Result<i32, DatabaseError<'a>> {
+        let exit_code = self.run_dependencies(name, script, args, context)?;
+
+        // Don't start the service, if a dependency fails.
+        if exit_code != 0 {
+            return Ok(exit_code);
+        }
+
+        if let Status::Stopped(_) = service::status(name) {
+            let pid = service::start(name, script.command(), &self.extra_paths())
+                .map_err(DatabaseError::Service)?;
+
+            context.started.push(name);
+
+            // Don't leak the service, if the script is interrupted.
+            service::stop_on_interrupt(pid);
+
+            events::emit(
+                "service_started",
+                &[("name", json::string(name)), ("pid", pid.to_string())],
+            );
+
+            println!(
+                "{} {} {}",
+                "up".green(),
+                name.yellow(),
+                format!("(pid {})", pid).yellow()
+            );
+        }
+
+        if let Some(condition) = script.ready_when() {
+            let start_time = Instant::now();
+
+            println!("{} {}", "waiting for".yellow(), name.green());
+
+            events::emit(
+                "waiting",
+                &[
+                    ("name", json::string(name)),
+                    ("condition", json::string(condition)),
+                ],
+            );
+
+            let result = ready::wait(condition, Some(name), timeout_of(script));
+
+            trace::span(
+                &format!("waiting for {}", name),
+                "service",
+                start_time,
+                &[("condition", json::string(condition))],
+            );
+
+            result.map_err(DatabaseError::NotReady)?;
+
+            events::emit(
+                "service_ready",
+                &[
+                    ("name", json::string(name)),
+                    ("duration_ms", start_time.elapsed().as_millis().to_string()),
+                ],
+            );
+
+            println!(
+                "{} {} {}",
+                name.yellow(),
+                "is ready in".green(),
+                format!("{:.2?}", start_time.elapsed()).yellow()
+            );
+        }
+
+        Ok(0)
+    }
+}
+
+/// Returns `true` if the run is significantly slower than the typical duration.
+///
+/// It must be at least 50% and a second longer, so the small differences are ignored.
+fn is_slow(duration: Duration, typical: Duration) -> bool {
+    duration.as_secs_f64() >= typical.as_secs_f64() * SLOWDOWN_RATIO
+        && duration.saturating_sub(typical) >= Duration::from_secs(1)
+}
+
+/// Returns the time to wait for the conditions of the script.
+fn timeout_of(script: &Script) -> Duration {
+    script
+        .timeout()
+        .and_then(ready::parse_duration)
+        .unwrap_or(DEFAULT_TIMEOUT)
+}
+
+/// Adds the run of the script to the history.
+fn record(
+    name: &str,
+    script: &Script,
+    script_args: &[String],
+    direct: bool,
+    start: i64,
+    duration: Duration,
+    exit_code: i32,
+) {
+    let entry = Entry {
+        start,
+        duration: duration.as_millis() as u64,
+        exit_code,
+        direct,
+        name: name.to_string(),
+        command: script::with_args(script.command(), script_args),
+        cwd: env::current_dir()
+            .map(|cwd| cwd.display().to_string())
+            .unwrap_or_default(),
+        args: script_args.to_vec(),
+    };
+
+    // We don't need to be warned, cuz the history isn't needed to run the script.
+    #[allow(unused_must_use)]
+    {
+        history::record(&entry);
+    }
+}
+
+#[test]
+fn test() {
+    let db = Database::parse_run_yaml(
+        "all:\n  depends: x, y, z\n  parallel: true\nx:\n  command: echo x\n  depends: b\ny:\n  command: echo y\n  depends: b, x\nz: echo z\nb: echo b\n",
+    )
+    .ok()
+    .unwrap();
+
+    let (_, all) = db.get("all").ok().unwrap();
+
+    let dependencies: Vec<_> = all
+        .depends()
+        .map(|name| db.get(name).ok().unwrap())
+        .collect();
+
+    let names = |done: HashSet<&'static str>| -> Vec<String> {
+        db.shared_dependencies(&dependencies, &done)
+            .ok()
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    };
+
+    // The shared dependency must be run before the parallel ones, and so must a parallel one needed by another.
+    assert_eq!(names(HashSet::new()), ["b", "x"]);
+
+    // The dependencies which are already run must be skipped.
+    assert_eq!(names(HashSet::from(["b"])), ["x"]);
+
+    // A script which isn't confirmed mustn't run its dependencies.
+    let db = Database::parse_run_yaml(
+        "a:\n  command: echo a\n  confirm: Sure?\n  depends: b\nb: echo b\n",
+    )
+    .ok()
+    .unwrap();
+
+    let (name, script) = db.get("a").ok().unwrap();
+    let mut context = Context::default();
+
+    assert!(matches!(
+        db.execute_script(name, script, &[], &Args::default(), &mut context),
+        Err(DatabaseError::NotConfirmed("a"))
+    ));
+    assert!(context.done.is_empty());
+}
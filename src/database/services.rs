@@ -0,0 +1,140 @@
+use crate::{
+    beautify::Beautify,
+    script::Script,
+    service::{self, Status},
+    state,
+};
+
+use super::{db::Database, errors::DatabaseError};
+
+impl<'a> Database<'a> {
+    /// Returns the services with the given aliases or names.
+    ///
+    /// If no alias or name is given, returns all the services.
+    fn services(
+        &'a self,
+        aliases_or_names: &'a [String],
+    ) -> Result<Vec<(&'a str, &'a Script<'a>)>, DatabaseError<'a>> {
+        if aliases_or_names.is_empty() {
+            return Ok(self
+                .scripts()
+                .filter(|(_, script)| script.service())
+                .collect());
+        }
+
+        aliases_or_names
+            .iter()
+            .map(|alias_or_name| self.service(alias_or_name))
+            .collect()
+    }
+
+    /// Returns the service with the given alias or name.
+    fn service(
+        &'a self,
+        alias_or_name: &'a str,
+    ) -> Result<(&'a str, &'a Script<'a>), DatabaseError<'a>> {
+        let (name, script) = self.get(alias_or_name)?;
+
+        if script.service() {
+            Ok((name, script))
+        } else {
+            Err(DatabaseError::NotService(name))
+        }
+    }
+
+    /// Starts the given services in background, or all of them if none is given.
+    pub fn up(&'a self, aliases_or_names: &'a [String]) -> Result<(), DatabaseError<'a>> {
+        for (name, script) in self.services(aliases_or_names)? {
+            match service::status(name) {
+                Status::Running(pid, _) => println!(
+                    "{} {} {}",
+                    name.yellow(),
+                    "is already running".green(),
+                    format!("(pid {})", pid).yellow()
+                ),
+
+                Status::Stopped(_) => {
+                    let pid = service::start(name, script.command(), &self.extra_paths())
+                        .map_err(DatabaseError::Service)?;
+
+                    println!(
+                        "{} {} {}",
+                        "up".green(),
+                        name.yellow(),
+                        format!("(pid {})", pid).yellow()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stops the given services, or all of them if none is given.
+    pub fn down(&'a self, aliases_or_names: &'a [String]) -> Result<(), DatabaseError<'a>> {
+        for (name, _) in self.services(aliases_or_names)? {
+            match service::stop(name).map_err(DatabaseError::Service)? {
+                true => println!("{} {}", "down".green(), name.yellow()),
+                false => println!("{} {}", name.yellow(), "isn't running".green()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restarts the given services, or all of them if none is given.
+    pub fn restart(&'a self, aliases_or_names: &'a [String]) -> Result<(), DatabaseError<'a>> {
+        for (name, _) in self.services(aliases_or_names)? {
+            service::stop(name).map_err(DatabaseError::Service)?;
+        }
+
+        self.up(aliases_or_names)
+    }
+
+    /// Prints the status of all the services.
+    pub fn ps(&'a self) -> Result<(), DatabaseError<'a>> {
+        let services = self.services(&[])?;
+
+        // Get the length of the longest name, to align the statuses.
+        let width = services
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut content = "Services:\n".green().to_string();
+
+        for (name, _) in services {
+            let status = service::status(name);
+
+            let name = format!("{:<width$}", name, width = width);
+
+            content += &match status {
+                Status::Running(pid, since) => format!(
+                    "    {}  {}  {}\n",
+                    name.yellow(),
+                    "running".green(),
+                    format!(
+                        "pid {}, up {}",
+                        pid,
+                        service::format_secs(state::now().saturating_sub(since))
+                    )
+                    .yellow()
+                ),
+
+                Status::Stopped(Some(exit_code)) => format!(
+                    "    {}  {}  {}\n",
+                    name.yellow(),
+                    "stopped".red(),
+                    format!("last exit {}", exit_code).yellow()
+                ),
+
+                Status::Stopped(None) => format!("    {}  {}\n", name.yellow(), "stopped".red()),
+            };
+        }
+
+        println!("{}", content);
+
+        Ok(())
+    }
+}
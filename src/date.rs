@@ -0,0 +1,99 @@
+use std::{
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A date and time in UTC.
+pub struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    millis: u32,
+}
+
+impl Date {
+    /// Returns the current date and time.
+    pub fn now() -> Date {
+        Date::from_unix_millis(unix_millis())
+    }
+
+    /// Creates a `Date` from milliseconds since Unix epoch.
+    pub fn from_unix_millis(millis: i64) -> Date {
+        let secs = millis.div_euclid(1000);
+        let days = secs.div_euclid(86400);
+        let secs_of_day = secs.rem_euclid(86400) as u32;
+
+        // Convert the days since Unix epoch to a civil date.
+        // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Date {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3600,
+            minute: secs_of_day % 3600 / 60,
+            second: secs_of_day % 60,
+            millis: millis.rem_euclid(1000) as u32,
+        }
+    }
+
+    /// Formats the date to be used as a file name, which is sorted chronologically, like `2023-01-31_18-04-05-123`.
+    pub fn to_file_name(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}-{:03}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second, self.millis
+        )
+    }
+}
+
+// Implement `Display` trait for `Date`, formatting like `2023-01-31T18:04:05.123Z`.
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second, self.millis
+        )
+    }
+}
+
+/// Returns the current time, as milliseconds since Unix epoch.
+pub fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[test]
+fn test() {
+    // Check the beginning of Unix epoch.
+    assert_eq!(
+        Date::from_unix_millis(0).to_string(),
+        "1970-01-01T00:00:00.000Z"
+    );
+
+    // Check a leap day.
+    assert_eq!(
+        Date::from_unix_millis(951_782_400_000).to_string(),
+        "2000-02-29T00:00:00.000Z"
+    );
+
+    // Check a date with time.
+    let date = Date::from_unix_millis(1_675_188_245_123);
+    assert_eq!(date.to_string(), "2023-01-31T18:04:05.123Z");
+    assert_eq!(date.to_file_name(), "2023-01-31_18-04-05-123");
+}
@@ -0,0 +1,152 @@
+use std::{
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::Mutex,
+};
+
+use crate::{
+    beautify::Beautify,
+    date, json,
+    output::{self, Sink, Stream},
+};
+
+/// The writer which the events are written to, if the events are enabled.
+static WRITER: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+/// The error type for the events.
+pub enum EventsError {
+    UnknownFormat(String),
+    NoTarget,
+    CannotBeOpened(String),
+}
+
+impl Display for EventsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat(format) => write!(
+                f,
+                "{} {}{} {}",
+                "the events can't be written as".red(),
+                format.yellow(),
+                ",".red(),
+                "use `--events=json:FILE`".red()
+            ),
+            Self::NoTarget => write!(
+                f,
+                "{} {} {} {}",
+                "the events need a file, use".red(),
+                "--events=json:FILE".yellow(),
+                "or".red(),
+                "--events=json:fd:N".yellow()
+            ),
+            Self::CannotBeOpened(target) => write!(
+                f,
+                "{} {}",
+                "the events can't be written to".red(),
+                target.yellow()
+            ),
+        }
+    }
+}
+
+/// Enables the events, which are given like `json:events.ndjson` or `json:fd:3`.
+///
+/// A file or a file descriptor is needed, so the events aren't mixed with the output of the scripts.
+pub fn init(events: &str) -> Result<(), EventsError> {
+    let (format, target) = events.split_once(':').unwrap_or((events, ""));
+
+    if format != "json" {
+        return Err(EventsError::UnknownFormat(format.to_string()));
+    }
+
+    if target.is_empty() {
+        return Err(EventsError::NoTarget);
+    }
+
+    // File descriptors are opened through `/dev/fd`, which is available on Linux and macOS.
+    let file = match target.strip_prefix("fd:") {
+        Some(fd) => OpenOptions::new()
+            .append(true)
+            .open(format!("/dev/fd/{}", fd)),
+        None => File::create(target),
+    };
+
+    let writer: Box<dyn Write + Send> =
+        Box::new(file.map_err(|_| EventsError::CannotBeOpened(target.to_string()))?);
+
+    if let Ok(mut current) = WRITER.lock() {
+        *current = Some(writer);
+    }
+
+    Ok(())
+}
+
+/// Writes an event as a line of JSON, if the events are enabled.
+///
+/// The values of the fields must already be JSON, like the ones created by `json::string`.
+pub fn emit(event: &str, fields: &[(&str, String)]) {
+    let Ok(mut writer) = WRITER.lock() else {
+        return;
+    };
+
+    let Some(writer) = writer.as_mut() else {
+        return;
+    };
+
+    let mut line = format!(
+        "{{\"event\":{},\"time\":{}",
+        json::string(event),
+        date::unix_millis()
+    );
+
+    for (key, value) in fields {
+        line += &format!(",{}:{}", json::string(key), value);
+    }
+
+    line += "}\n";
+
+    // We don't need to be warned, cuz the events can't break the scripts.
+    #[allow(unused_must_use)]
+    {
+        writer.write_all(line.as_bytes());
+        writer.flush();
+    }
+}
+
+/// Returns `true` if the events are enabled.
+pub fn enabled() -> bool {
+    WRITER.lock().is_ok_and(|writer| writer.is_some())
+}
+
+/// The sink which emits the output of a script as events.
+pub struct Events<'a> {
+    /// The name of the script.
+    name: &'a str,
+}
+
+impl<'a> Events<'a> {
+    /// Creates a sink for the output of the script.
+    pub fn new(name: &'a str) -> Events<'a> {
+        Events { name }
+    }
+}
+
+// Implement `Sink` trait for `Events`.
+impl Sink for Events<'_> {
+    fn line(&mut self, stream: Stream, line: &str) {
+        let stream = match stream {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        };
+
+        emit(
+            "output",
+            &[
+                ("name", json::string(self.name)),
+                ("stream", json::string(stream)),
+                ("line", json::string(&output::strip_ansi(line))),
+            ],
+        );
+    }
+}
@@ -1,25 +1,42 @@
+use crate::{events, json, output, report, trace};
+
 /// The trait that enables getting the value inside a `Result`.
-/// 
+///
 pub trait Exit<T> {
     fn exit(self) -> T;
 }
 
-
 // Implement `Exit<T>` for `Result<T, E>`.
 impl<T, E: ToString> Exit<T> for Result<T, E> {
     /// If it's `Ok(T)`, returns `T` inside `Result<T, E>`.
-    /// 
+    ///
     /// If it's `Err(E)`, prints `E.to_string()` to standard error,
     /// and terminates the process.
-    /// 
+    ///
     fn exit(self) -> T {
         match self {
             Ok(value) => value,
             Err(err) => {
-                eprintln!("{}", err.to_string());
+                let message = err.to_string();
+
+                events::emit(
+                    "error",
+                    &[("message", json::string(&output::strip_ansi(&message)))],
+                );
+
+                eprintln!("{}", message);
+
+                // Save the trace and the report of what is done until the error.
+                if let Err(err) = trace::save() {
+                    eprintln!("{}", err);
+                }
+
+                if let Err(err) = report::save() {
+                    eprintln!("{}", err);
+                }
+
                 std::process::exit(1);
             }
         }
     }
 }
-
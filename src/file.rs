@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use crate::beautify::Beautify;
 
 /// The type that represents file names.
@@ -16,21 +18,29 @@ pub enum FileError {
     Exists(FileName),
 }
 
-// Implement `ToString` trait for `FileError`.
-impl ToString for FileError {
-    fn to_string(&self) -> String {
+// Implement `Display` trait for `FileError`.
+impl Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::CannotBeRead(file_name) => {
-                format!("{} {}", "can't read".red(), file_name.green())
+                write!(f, "{} {}", "can't read".red(), file_name.green())
             }
 
             Self::CannotBeWritten(file_name) => {
-                format!("{} {}", "can't write to".red(), file_name.green())
+                write!(f, "{} {}", "can't write to".red(), file_name.green())
             }
 
-            Self::Exists(file_name) => format!("{} {}", file_name.green(), "already exists".red(),),
+            Self::Exists(file_name) => write!(
+                f,
+                "{} {}\n\n{}",
+                file_name.green(),
+                "already exists".red(),
+                "type `run -i --merge` to import the scripts into it, or `run -i --force` to overwrite it"
+                    .yellow()
+            ),
 
-            Self::NotFound(file_name) => format!(
+            Self::NotFound(file_name) => write!(
+                f,
                 "{} {}\n\n{}",
                 file_name.green(),
                 "isn't found".red(),
@@ -72,7 +82,7 @@ pub fn write(file_name: FileName, content: &str) -> Result<()> {
 ///
 pub fn create(file_name: FileName, content: &str) -> Result<()> {
     if exists(file_name) {
-        return Err(FileError::Exists(file_name));
+        Err(FileError::Exists(file_name))
     } else {
         write(file_name, content)
     }
@@ -0,0 +1,242 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{Result, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::{json, state};
+
+/// The number of entries kept in the history, when it gets twice as long.
+const KEEP: usize = 1000;
+
+/// The number of the latest successful runs, used to find the typical duration of a script.
+const SAMPLES: usize = 20;
+
+/// The number of successful runs needed, before a duration is considered typical.
+const MIN_SAMPLES: usize = 3;
+
+/// A record of a script which is executed.
+pub struct Entry {
+    /// The time the script is started, as milliseconds since Unix epoch.
+    pub start: i64,
+    /// The time the script takes, in milliseconds.
+    pub duration: u64,
+    /// The exit code of the script.
+    pub exit_code: i32,
+    /// Whether the script is run directly, not as a dependency.
+    pub direct: bool,
+    /// The name of the script.
+    pub name: String,
+    /// The command of the script.
+    pub command: String,
+    /// The directory the script is executed in.
+    pub cwd: String,
+    /// The arguments given to the script.
+    pub args: Vec<String>,
+}
+
+impl Entry {
+    /// Converts the entry to a JSON object.
+    pub fn to_json(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|arg| json::string(arg)).collect();
+
+        format!(
+            "{{\"start\":{},\"duration\":{},\"exit_code\":{},\"direct\":{},\"name\":{},\"command\":{},\"cwd\":{},\"args\":[{}]}}",
+            self.start,
+            self.duration,
+            self.exit_code,
+            self.direct,
+            json::string(&self.name),
+            json::string(&self.command),
+            json::string(&self.cwd),
+            args.join(",")
+        )
+    }
+
+    /// Converts the entry to a line, having its fields separated by tabs.
+    fn to_line(&self) -> String {
+        let mut fields = vec![
+            self.start.to_string(),
+            self.duration.to_string(),
+            self.exit_code.to_string(),
+            (self.direct as u8).to_string(),
+            escape(&self.name),
+            escape(&self.command),
+            escape(&self.cwd),
+        ];
+
+        fields.extend(self.args.iter().map(|arg| escape(arg)));
+
+        fields.join("\t")
+    }
+
+    /// Parses a line created by `Entry::to_line`.
+    fn from_line(line: &str) -> Option<Entry> {
+        let mut fields = line.split('\t');
+
+        Some(Entry {
+            start: fields.next()?.parse().ok()?,
+            duration: fields.next()?.parse().ok()?,
+            exit_code: fields.next()?.parse().ok()?,
+            direct: fields.next()? == "1",
+            name: unescape(fields.next()?),
+            command: unescape(fields.next()?),
+            cwd: unescape(fields.next()?),
+            args: fields.map(unescape).collect(),
+        })
+    }
+}
+
+/// Returns the path of the history file, without creating anything.
+fn path() -> PathBuf {
+    state::path("history")
+}
+
+/// Adds the entry to the end of the history.
+pub fn record(entry: &Entry) -> Result<()> {
+    // `.run` is only created when the history is written.
+    state::dir("")?;
+
+    let path = path();
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+    writeln!(file, "{}", entry.to_line())?;
+
+    // Keep the history short, by removing the oldest entries once in a while.
+    let content = fs::read_to_string(&path)?;
+    let lines: Vec<&str> = content.lines().collect();
+
+    if lines.len() > KEEP * 2 {
+        fs::write(&path, lines[lines.len() - KEEP..].join("\n") + "\n")?;
+    }
+
+    Ok(())
+}
+
+/// Returns all the entries in the history, from the oldest to the newest.
+pub fn read() -> Vec<Entry> {
+    fs::read_to_string(path())
+        .map(|content| content.lines().filter_map(Entry::from_line).collect())
+        .unwrap_or_default()
+}
+
+/// Returns the median duration of the latest successful runs of the script, if it has run enough.
+pub fn typical(entries: &[Entry], name: &str) -> Option<Duration> {
+    let mut durations: Vec<u64> = entries
+        .iter()
+        .rev()
+        .filter(|entry| entry.name == name && entry.exit_code == 0)
+        .take(SAMPLES)
+        .map(|entry| entry.duration)
+        .collect();
+
+    if durations.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    durations.sort_unstable();
+
+    let middle = durations.len() / 2;
+
+    let median = match durations.len() % 2 {
+        0 => (durations[middle - 1] + durations[middle]) / 2,
+        _ => durations[middle],
+    };
+
+    Some(Duration::from_millis(median))
+}
+
+/// Escapes the tabs, line breaks, and backslashes in the text.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Reverts `escape`.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(ch) => unescaped.push(ch),
+            None => (),
+        }
+    }
+
+    unescaped
+}
+
+#[test]
+fn test() {
+    // Create a test entry.
+    let entry = Entry {
+        start: 1_675_188_245_123,
+        duration: 1234,
+        exit_code: 1,
+        direct: true,
+        name: "build".to_string(),
+        command: "cargo build\t# with a tab\nand a line".to_string(),
+        cwd: "C:\\projects\\app".to_string(),
+        args: vec!["--release".to_string(), "".to_string()],
+    };
+
+    // The entry must stay the same after converting it to a line and back.
+    let parsed = Entry::from_line(&entry.to_line()).unwrap();
+
+    assert_eq!(parsed.to_json(), entry.to_json());
+    assert_eq!(parsed.command, entry.command);
+    assert_eq!(parsed.cwd, entry.cwd);
+    assert_eq!(parsed.args, entry.args);
+}
+
+#[test]
+fn test_typical() {
+    let entry = |name: &str, duration: u64, exit_code: i32| Entry {
+        start: 0,
+        duration,
+        exit_code,
+        direct: true,
+        name: name.to_string(),
+        command: String::new(),
+        cwd: String::new(),
+        args: vec![],
+    };
+
+    // Failed runs and other scripts must be ignored.
+    let entries = vec![
+        entry("build", 1000, 0),
+        entry("build", 9000, 1),
+        entry("test", 9000, 0),
+        entry("build", 3000, 0),
+    ];
+
+    assert_eq!(typical(&entries, "build"), None);
+
+    let mut entries = entries;
+    entries.push(entry("build", 2000, 0));
+
+    assert_eq!(
+        typical(&entries, "build"),
+        Some(Duration::from_millis(2000))
+    );
+
+    entries.push(entry("build", 4000, 0));
+
+    assert_eq!(
+        typical(&entries, "build"),
+        Some(Duration::from_millis(2500))
+    );
+}
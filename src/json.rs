@@ -0,0 +1,571 @@
+use std::borrow::Cow;
+
+/// The maximum depth of the arrays and the objects, so a deeply nested text can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A JSON value, whose strings are borrowed from the text unless they have escapes.
+///
+/// The numbers and the booleans are only validated, as the importers don't use their values.
+pub enum Value<'a> {
+    Null,
+    Bool,
+    Number,
+    String(Cow<'a, str>),
+    Array(Vec<Value<'a>>),
+    /// The keys, the values, and the line numbers of the keys, in order.
+    Object(Vec<(Cow<'a, str>, Value<'a>, usize)>),
+}
+
+impl<'a> Value<'a> {
+    /// Returns the value of the key and the line number of the key, if it's an object having the key.
+    ///
+    /// If the key is repeated, the last one is returned, like in JavaScript.
+    pub fn get(&self, key: &str) -> Option<(&Value<'a>, usize)> {
+        match self {
+            Self::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(member_key, _, _)| member_key == key)
+                .map(|(_, value, line_no)| (value, *line_no)),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if it's a string.
+    pub fn as_str(&self) -> Option<&Cow<'a, str>> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+/// The error for an invalid JSON text, with the line and the column it's found at.
+pub struct JsonError {
+    pub line_no: usize,
+    pub column_no: usize,
+}
+
+/// Parses the JSON text.
+pub fn parse(text: &str) -> Result<Value<'_>, JsonError> {
+    parse_with(text, false)
+}
+
+/// Parses the JSON text which may have comments and trailing commas, like `deno.jsonc`.
+pub fn parse_jsonc(text: &str) -> Result<Value<'_>, JsonError> {
+    parse_with(text, true)
+}
+
+/// Parses the JSON text, allowing comments and trailing commas if `jsonc` is `true`.
+fn parse_with(text: &str, jsonc: bool) -> Result<Value<'_>, JsonError> {
+    let mut parser = Parser {
+        text,
+        pos: 0,
+        line_no: 1,
+        line_start: 0,
+        depth: 0,
+        jsonc,
+    };
+
+    parser.skip_whitespace();
+
+    let value = parser.parse_value()?;
+
+    parser.skip_whitespace();
+
+    // Nothing can come after the value.
+    if parser.pos < text.len() {
+        return Err(parser.error());
+    }
+
+    Ok(value)
+}
+
+/// A recursive descent parser for JSON.
+struct Parser<'a> {
+    /// The JSON text.
+    text: &'a str,
+    /// The index of the next byte to be read.
+    pos: usize,
+    /// The line number of the next byte to be read.
+    line_no: usize,
+    /// The index of the first byte of the current line.
+    line_start: usize,
+    /// The number of the arrays and the objects the next byte is in.
+    depth: usize,
+    /// Whether comments and trailing commas are allowed.
+    jsonc: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns the next byte, without reading it.
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    /// Returns an error at the current position.
+    fn error(&self) -> JsonError {
+        JsonError {
+            line_no: self.line_no,
+            column_no: self.text[self.line_start..self.pos].chars().count() + 1,
+        }
+    }
+
+    /// Skips the given number of bytes, which may have line breaks, like a block comment.
+    fn skip(&mut self, len: usize) {
+        let skipped = &self.text[self.pos..self.pos + len];
+
+        if let Some(index) = skipped.rfind('\n') {
+            self.line_no += skipped.matches('\n').count();
+            self.line_start = self.pos + index + 1;
+        }
+
+        self.pos += len;
+    }
+
+    /// Enters an array or an object, returning an error if it's nested too deep.
+    fn enter(&mut self) -> Result<(), JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error());
+        }
+
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    /// Reads the byte if it's the expected one, else returns an error.
+    fn expect(&mut self, expected: u8) -> Result<(), JsonError> {
+        match self.peek() {
+            Some(byte) if byte == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Skips the whitespaces, and the comments if they are allowed.
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = &self.text[self.pos..];
+
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.skip(1),
+
+                // Line comments end with a line break.
+                _ if self.jsonc && rest.starts_with("//") => {
+                    self.pos += rest.find('\n').unwrap_or(rest.len());
+                }
+
+                // Block comments end with `*/`, an unterminated one is left to be an error.
+                _ if self.jsonc && rest.starts_with("/*") => match rest[2..].find("*/") {
+                    Some(index) => self.skip(index + 4),
+                    None => return,
+                },
+
+                _ => return,
+            }
+        }
+    }
+
+    /// Reads a comma and returns `true` if the closing bracket follows it, which is only allowed if trailing commas are allowed.
+    fn is_trailing_comma(&mut self, closing: u8) -> bool {
+        self.pos += 1;
+
+        if !self.jsonc {
+            return false;
+        }
+
+        self.skip_whitespace();
+
+        self.peek() == Some(closing)
+    }
+
+    /// Parses a value, which begins at the current position.
+    fn parse_value(&mut self) -> Result<Value<'a>, JsonError> {
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => Ok(Value::String(self.parse_string()?)),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b't') => self.parse_literal("true", Value::Bool),
+            Some(b'f') => self.parse_literal("false", Value::Bool),
+            Some(b'n') => self.parse_literal("null", Value::Null),
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Parses a literal, like `true`.
+    fn parse_literal(&mut self, literal: &str, value: Value<'a>) -> Result<Value<'a>, JsonError> {
+        if !self.text[self.pos..].starts_with(literal) {
+            return Err(self.error());
+        }
+
+        self.pos += literal.len();
+
+        Ok(value)
+    }
+
+    /// Parses an object, like `{"key": "value"}`.
+    fn parse_object(&mut self) -> Result<Value<'a>, JsonError> {
+        self.enter()?;
+
+        let object = self.parse_members();
+
+        self.depth -= 1;
+
+        object
+    }
+
+    /// Parses the members of an object, with its braces.
+    fn parse_members(&mut self) -> Result<Value<'a>, JsonError> {
+        let mut members = vec![];
+
+        self.expect(b'{')?;
+        self.skip_whitespace();
+
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            let line_no = self.line_no;
+            let key = self.parse_string()?;
+
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.skip_whitespace();
+
+            let value = self.parse_value()?;
+
+            members.push((key, value, line_no));
+
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') if self.is_trailing_comma(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                Some(b',') => (),
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// Parses an array, like `["a", "b"]`.
+    fn parse_array(&mut self) -> Result<Value<'a>, JsonError> {
+        self.enter()?;
+
+        let array = self.parse_values();
+
+        self.depth -= 1;
+
+        array
+    }
+
+    /// Parses the values of an array, with its brackets.
+    fn parse_values(&mut self) -> Result<Value<'a>, JsonError> {
+        let mut values = vec![];
+
+        self.expect(b'[')?;
+        self.skip_whitespace();
+
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            values.push(self.parse_value()?);
+
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') if self.is_trailing_comma(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                Some(b',') => (),
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// Parses a number, like `-1.5e3`.
+    fn parse_number(&mut self) -> Result<Value<'a>, JsonError> {
+        let digits = |parser: &mut Parser| {
+            let start = parser.pos;
+
+            while let Some(b'0'..=b'9') = parser.peek() {
+                parser.pos += 1;
+            }
+
+            parser.pos > start
+        };
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+
+        // Numbers can't have leading zeros.
+        if self.peek() == Some(b'0') {
+            self.pos += 1;
+        } else if !digits(self) {
+            return Err(self.error());
+        }
+
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+
+            if !digits(self) {
+                return Err(self.error());
+            }
+        }
+
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+
+            if !digits(self) {
+                return Err(self.error());
+            }
+        }
+
+        Ok(Value::Number)
+    }
+
+    /// Parses a string, like `"a \"quoted\" word"`.
+    ///
+    /// The string is borrowed from the text, unless it has escapes.
+    fn parse_string(&mut self) -> Result<Cow<'a, str>, JsonError> {
+        self.expect(b'"')?;
+
+        let start = self.pos;
+        let mut unescaped: Option<String> = None;
+
+        loop {
+            let Some(ch) = self.text[self.pos..].chars().next() else {
+                return Err(self.error());
+            };
+
+            match ch {
+                '"' => {
+                    let string = match unescaped {
+                        Some(unescaped) => Cow::Owned(unescaped),
+                        None => Cow::Borrowed(&self.text[start..self.pos]),
+                    };
+
+                    self.pos += 1;
+
+                    return Ok(string);
+                }
+
+                '\\' => {
+                    let unescaped =
+                        unescaped.get_or_insert_with(|| self.text[start..self.pos].to_string());
+
+                    self.pos += 1;
+
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.pos += 1;
+
+                            let ch = self.parse_unicode_escape()?;
+
+                            unescaped.push(ch);
+
+                            continue;
+                        }
+                        _ => return Err(self.error()),
+                    };
+
+                    unescaped.push(escaped);
+
+                    self.pos += 1;
+                }
+
+                // Control characters must be escaped.
+                ch if ch < ' ' => return Err(self.error()),
+
+                ch => {
+                    if let Some(unescaped) = &mut unescaped {
+                        unescaped.push(ch);
+                    }
+
+                    self.pos += ch.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Parses the hexadecimal digits of a `\u` escape, after `\u`.
+    ///
+    /// The characters outside of the Basic Multilingual Plane are written as two escapes, like `\ud83d\ude00`.
+    fn parse_unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.parse_hex()?;
+
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error());
+        }
+
+        // A high surrogate must be followed by a low one.
+        if !self.text[self.pos..].starts_with("\\u") {
+            return Err(self.error());
+        }
+
+        self.pos += 2;
+
+        let low = self.parse_hex()?;
+
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error());
+        }
+
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| self.error())
+    }
+
+    /// Parses 4 hexadecimal digits.
+    fn parse_hex(&mut self) -> Result<u32, JsonError> {
+        let hex = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error())?;
+
+        if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(self.error());
+        }
+
+        self.pos += 4;
+
+        u32::from_str_radix(hex, 16).map_err(|_| self.error())
+    }
+}
+
+/// Converts the text to a JSON string, with quotes around it.
+pub fn string(text: &str) -> String {
+    let mut string = String::with_capacity(text.len() + 2);
+
+    string.push('"');
+
+    for ch in text.chars() {
+        match ch {
+            '"' => string += "\\\"",
+            '\\' => string += "\\\\",
+            '\n' => string += "\\n",
+            '\r' => string += "\\r",
+            '\t' => string += "\\t",
+            ch if ch < ' ' => string += &format!("\\u{:04x}", ch as u32),
+            ch => string.push(ch),
+        }
+    }
+
+    string.push('"');
+
+    string
+}
+
+#[test]
+fn test() {
+    assert_eq!(string("echo \"hi\""), r#""echo \"hi\"""#);
+    assert_eq!(string("a\\b\nc\u{1b}"), r#""a\\b\nc\u001b""#);
+}
+
+#[test]
+fn test_parse() {
+    let json = r#"{
+        "name": "app",
+        "config": { "scripts": { "wrong": "nested" } },
+        "scripts": {
+            "lint": "eslint \"src/**/*.ts\"",
+            "emoji": "echo \u00e9 \ud83d\ude00 \\ \/",
+            "nested": { "a": [1, -2.5e3, true, false, null] }
+        }
+    }"#;
+
+    let value = parse(json).ok().unwrap();
+
+    let (scripts, line_no) = value.get("scripts").unwrap();
+
+    // Only the top-level key is found.
+    assert_eq!(line_no, 4);
+    assert!(scripts.get("wrong").is_none());
+
+    // The strings are unescaped, and borrowed if they have no escapes.
+    let (lint, _) = scripts.get("lint").unwrap();
+    assert_eq!(lint.as_str().unwrap(), "eslint \"src/**/*.ts\"");
+
+    let (emoji, _) = scripts.get("emoji").unwrap();
+    assert_eq!(emoji.as_str().unwrap(), "echo é 😀 \\ /");
+
+    let (name, _) = value.get("name").unwrap();
+    assert!(matches!(name.as_str(), Some(Cow::Borrowed("app"))));
+
+    // The errors have the line and the column.
+    let error = |json: &str| parse(json).err().map(|err| (err.line_no, err.column_no));
+
+    assert_eq!(error("{\n  \"a\": 01\n}"), Some((2, 9)));
+    assert_eq!(error("{\"a\": \"b\",}"), Some((1, 11)));
+    assert_eq!(error("{\"a\": \"\\x\"}"), Some((1, 9)));
+    assert_eq!(error("{\"a\": 1} 2"), Some((1, 10)));
+    assert_eq!(error("[1, 2"), Some((1, 6)));
+    assert_eq!(error(r#""é\u12""#), Some((1, 5)));
+
+    // Comments and trailing commas are only allowed in JSONC.
+    let jsonc = r#"{
+        // A line comment.
+        "tasks": { /* A block comment. */ "a": ["b", "c",], },
+    }"#;
+
+    assert_eq!(error(jsonc), Some((2, 9)));
+
+    let value = parse_jsonc(jsonc).ok().unwrap();
+    let (tasks, _) = value.get("tasks").unwrap();
+
+    assert!(matches!(tasks.get("a"), Some((Value::Array(values), 3)) if values.len() == 2));
+
+    let error = |json: &str| {
+        parse_jsonc(json)
+            .err()
+            .map(|err| (err.line_no, err.column_no))
+    };
+
+    assert_eq!(error("{\"a\": 1 /* unterminated }"), Some((1, 9)));
+    assert_eq!(error("[1,,]"), Some((1, 4)));
+
+    // The lines must be counted in the block comments too.
+    assert_eq!(error("/* a\n\n */ [1,\n 2,,]"), Some((4, 4)));
+
+    // The values nested too deep must be rejected, instead of overflowing the stack.
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+    assert!(parse(&nested(MAX_DEPTH)).is_ok());
+    assert_eq!(error(&nested(MAX_DEPTH + 1)), Some((1, MAX_DEPTH + 1)));
+    assert_eq!(error(&"[".repeat(100_000)), Some((1, MAX_DEPTH + 1)));
+}
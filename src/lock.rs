@@ -0,0 +1,190 @@
+use std::{
+    fmt::Display,
+    fs::{File, OpenOptions, TryLockError},
+    io::{Read, Seek, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{beautify::Beautify, state};
+
+/// The process holding a lock.
+pub struct Holder {
+    /// The process ID of the holder.
+    pid: u32,
+    /// The time the lock is taken, as seconds since Unix epoch.
+    since: u64,
+}
+
+// Implement `Display` trait for `Holder`.
+impl Display for Holder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let held_for = Duration::from_secs(state::now().saturating_sub(self.since));
+
+        write!(f, "pid {}, started {:?} ago", self.pid, held_for)
+    }
+}
+
+/// What a lock is taken for.
+#[derive(Clone, Copy)]
+pub enum Group<'g> {
+    /// The script with the given name, which is marked with `exclusive: true`.
+    Script(&'g str),
+    /// The group with the given name, which is shared by the scripts marked with `exclusive: <group>`.
+    Named(&'g str),
+}
+
+impl Group<'_> {
+    /// Returns the name of the script or the group.
+    fn name(&self) -> &str {
+        match self {
+            Self::Script(name) | Self::Named(name) => name,
+        }
+    }
+
+    /// Returns the name of the lock file, the scripts and the groups have separate namespaces.
+    fn file_name(&self) -> String {
+        match self {
+            Self::Script(name) => format!("script-{}.lock", state::file_name(name)),
+            Self::Named(name) => format!("group-{}.lock", state::file_name(name)),
+        }
+    }
+}
+
+/// An exclusive lock, which is released when it's dropped.
+pub struct Lock {
+    /// The locked file, the lock is released when it's closed.
+    _file: File,
+}
+
+/// The error type for lock operations.
+pub enum LockError {
+    /// The lock file is not able to be created or locked.
+    CannotBeTaken(String),
+    /// The lock is held by another process.
+    Held(String, Option<Holder>),
+}
+
+// Implement `Display` trait for `LockError`.
+impl Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CannotBeTaken(group) => write!(f, "{} {}", "can't lock".red(), group.green()),
+
+            Self::Held(group, holder) => {
+                write!(f, "{} {}", group.green(), "is already running".red())?;
+
+                if let Some(holder) = holder {
+                    write!(f, " {}", format!("({})", holder).yellow())?;
+                }
+
+                write!(f, "\n\n{}", "use `--wait` to wait for it".yellow())
+            }
+        }
+    }
+}
+
+/// Takes the exclusive lock of the given group in `.run/locks`.
+///
+/// If the lock is held by another process, waits for it if `wait` is `true`, otherwise fails.
+pub fn acquire(group: Group, wait: bool) -> Result<Lock, LockError> {
+    let dir =
+        state::dir("locks").map_err(|_| LockError::CannotBeTaken(group.name().to_string()))?;
+
+    acquire_in(&dir, group, wait)
+}
+
+/// Takes the exclusive lock of the given group in the given directory.
+fn acquire_in(dir: &Path, group: Group, wait: bool) -> Result<Lock, LockError> {
+    let cannot_be_taken = || LockError::CannotBeTaken(group.name().to_string());
+
+    let path = dir.join(group.file_name());
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|_| cannot_be_taken())?;
+
+    match file.try_lock() {
+        Ok(()) => (),
+
+        Err(TryLockError::WouldBlock) => {
+            let holder = read_holder(&mut file);
+
+            if !wait {
+                return Err(LockError::Held(group.name().to_string(), holder));
+            }
+
+            match holder {
+                Some(holder) => println!(
+                    "{} {} {}\n",
+                    "waiting for".yellow(),
+                    group.name().green(),
+                    format!("({})", holder).yellow()
+                ),
+                None => println!("{} {}\n", "waiting for".yellow(), group.name().green()),
+            }
+
+            file.lock().map_err(|_| cannot_be_taken())?;
+        }
+
+        Err(TryLockError::Error(_)) => return Err(cannot_be_taken()),
+    }
+
+    // Record the holder, so the others can know who holds the lock.
+    // We don't need to be warned, cuz the holder is only informative.
+    #[allow(unused_must_use)]
+    {
+        file.set_len(0);
+        file.rewind();
+        write!(file, "{}\n{}\n", std::process::id(), state::now());
+    }
+
+    Ok(Lock { _file: file })
+}
+
+/// Reads the holder written to the lock file, if any.
+fn read_holder(file: &mut File) -> Option<Holder> {
+    let mut content = String::new();
+
+    file.read_to_string(&mut content).ok()?;
+
+    let mut lines = content.lines();
+
+    Some(Holder {
+        pid: lines.next()?.parse().ok()?,
+        since: lines.next()?.parse().ok()?,
+    })
+}
+
+#[test]
+fn test() {
+    let dir = std::env::temp_dir().join(format!("run-lock-test-{}", std::process::id()));
+
+    std::fs::create_dir_all(&dir).ok().unwrap();
+
+    let lock = acquire_in(&dir, Group::Script("db"), false).ok().unwrap();
+
+    // The same script must not be locked twice.
+    assert!(matches!(
+        acquire_in(&dir, Group::Script("db"), false),
+        Err(LockError::Held(..))
+    ));
+
+    // A group with the same name must not conflict with the script.
+    let group_lock = acquire_in(&dir, Group::Named("db"), false).ok().unwrap();
+
+    // The names which used to share a lock file must not conflict.
+    let other_lock = acquire_in(&dir, Group::Named("a/b"), false).ok().unwrap();
+    assert!(acquire_in(&dir, Group::Named("a_b"), false).is_ok());
+
+    // The lock must be released when it's dropped.
+    drop(lock);
+    assert!(acquire_in(&dir, Group::Script("db"), false).is_ok());
+
+    drop((group_lock, other_lock));
+    std::fs::remove_dir_all(&dir).ok();
+}
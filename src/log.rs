@@ -0,0 +1,89 @@
+use std::{
+    fs::{self, File},
+    io::{Result, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    date::Date,
+    output::{self, Sink, Stream},
+    state,
+};
+
+/// The number of log files kept for each script, unless `log_keep` is given.
+pub const DEFAULT_KEEP: usize = 10;
+
+/// The log file of a run of a script.
+pub struct Log {
+    /// The file which the output is written to.
+    file: File,
+    /// Whether each line begins with the time it's written.
+    timestamps: bool,
+}
+
+impl Log {
+    /// Creates a new log file for the script in `.run/logs/<name>`.
+    ///
+    /// Only the newest `keep` log files of the script are kept, including the new one.
+    pub fn create(name: &str, command: &str, keep: usize, timestamps: bool) -> Result<Log> {
+        let dir = state::dir("logs")?.join(state::file_name(name));
+
+        fs::create_dir_all(&dir)?;
+
+        let mut file = File::create(dir.join(format!("{}.log", Date::now().to_file_name())))?;
+
+        writeln!(file, "$ {}", command)?;
+
+        // Remove the oldest log files, the names are sorted by time.
+        let mut paths = log_paths(&dir)?;
+
+        paths.sort();
+
+        for path in paths.iter().take(paths.len().saturating_sub(keep.max(1))) {
+            fs::remove_file(path)?;
+        }
+
+        Ok(Log { file, timestamps })
+    }
+
+    /// Writes the exit code and the elapsed time to the end of the log file.
+    pub fn finish(&mut self, exit_code: i32, elapsed: Duration) {
+        // We don't need to be warned, cuz the result won't affect any operation.
+        #[allow(unused_must_use)]
+        {
+            writeln!(self.file, "$ exit code {} in {:.2?}", exit_code, elapsed);
+        }
+    }
+}
+
+// Implement `Sink` trait for `Log`.
+impl Sink for Log {
+    fn line(&mut self, _: Stream, line: &str) {
+        let line = output::strip_ansi(line);
+
+        // We don't need to be warned, cuz the result won't affect any operation.
+        #[allow(unused_must_use)]
+        if self.timestamps {
+            writeln!(self.file, "[{}] {}", Date::now(), line);
+        } else {
+            writeln!(self.file, "{}", line);
+        }
+    }
+}
+
+/// Returns the path of the newest log file of the script, if any.
+pub fn latest(name: &str) -> Option<PathBuf> {
+    let dir = state::path("logs").join(state::file_name(name));
+
+    log_paths(&dir).ok()?.into_iter().max()
+}
+
+/// Returns the paths of the log files in the directory.
+fn log_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "log"))
+        .collect())
+}
@@ -1,94 +1,238 @@
-use std::{env::args_os, time::Instant};
+use std::time::Instant;
 
-use database::{Database, DENO_JSON, PACKAGE_JSON, RUN_YAML};
+use args::Args;
+use database::{
+    changed_message, read_manifests, read_sources, Database, InitFlags, InitMode, RUN_YAML,
+};
 
 use crate::exit::Exit;
+use file::FileError;
 
 use beautify::Beautify;
 
+mod args;
 mod beautify;
+mod ci;
 mod database;
+mod date;
+mod events;
 mod exit;
 mod file;
+mod history;
+mod json;
+mod lock;
+mod log;
+mod node;
+mod notify;
+mod npm;
+mod output;
+mod progress;
+mod prompt;
+mod ready;
+mod regex;
+mod report;
 mod script;
+mod secrets;
+mod service;
+mod settings;
+mod state;
+mod toolchain;
+mod trace;
+mod usage;
+
+/// The usages of run, with their descriptions.
+const USAGES: &[(&str, &str)] = &[
+    ("run", "Displays the scripts."),
+    (
+        "run [FLAGS] <SCRIPT NAME> [ARGS...]",
+        "Runs the script with the arguments.",
+    ),
+    ("run up [SERVICE...]", "Starts the services in background."),
+    ("run down [SERVICE...]", "Stops the services."),
+    ("run restart [SERVICE...]", "Restarts the services."),
+    ("run ps", "Displays the status of the services."),
+    (
+        "run logs <SCRIPT> [-f]",
+        "Displays the latest log of the script.",
+    ),
+    (
+        "run history [FILTER] [--json]",
+        "Displays the latest scripts run.",
+    ),
+    ("run last, run !!", "Runs the last script again."),
+    (
+        "run bench <SCRIPT...> [-n N]",
+        "Measures the durations of the scripts.",
+    ),
+    (
+        "run doctor",
+        "Displays the directories added to PATH, and why.",
+    ),
+    (
+        "run -i [--force | --merge] [--template NAME]",
+        "Imports the scripts of the manifests or a template to run.yaml.",
+    ),
+];
+
+/// The flags of run, with their descriptions.
+const FLAGS: &[(&str, &str)] = &[
+    ("--help, -h", "Displays a help message."),
+    ("--init, -i", "Creates a run.yaml file from the manifests."),
+    ("--yes, -y", "Confirms the script without asking."),
+    ("--wait, -w", "Waits for the script's lock, if it's held."),
+    ("--stats", "Displays the resources used by the script."),
+    (
+        "--events=json:FILE",
+        "Writes the events as JSON lines to a file, or fd:N.",
+    ),
+    (
+        "--trace <FILE>",
+        "Saves the timeline of the run as a trace file.",
+    ),
+    (
+        "--report FORMAT=FILE",
+        "Saves a report as junit or json, can be repeated.",
+    ),
+];
 
 fn main() {
-    // Get the first arg.
-    let first_arg = args_os().nth(1);
+    // Get the args.
+    let args = Args::parse().exit();
 
-    // Convert it to `Option<&str>`.
-    let param = first_arg.as_ref().and_then(|arg| arg.to_str());
+    // Enable the events, if they are asked for.
+    if let Some(events) = args.events() {
+        events::init(events).exit();
+    }
+
+    // Record a report, if it's asked for.
+    if !args.reports().is_empty() {
+        report::init(args.reports()).exit();
+    }
+
+    // Record a trace, if it's asked for.
+    if let Some(path) = args.trace() {
+        trace::init(path);
+    }
 
     // Match parameter.
-    match param {
+    match args.param() {
         // If initialization flag is set, initialize a new run.yaml file.
         Some("-i" | "--init") => {
             let start_time = Instant::now();
 
-            // If package.json file exists in current directory, generate a script database using package.json scripts.
-            if file::exists(PACKAGE_JSON) {
-                let package_json = file::read(PACKAGE_JSON).exit();
+            let flags = InitFlags::parse(args.rest()).exit();
+            let mode = flags.mode;
 
-                let db = Database::from_package_json(&package_json).exit();
+            let existed = file::exists(RUN_YAML);
 
-                db.save().exit();
+            // An existing run.yaml is only changed with `--force` or `--merge`.
+            if existed && mode == InitMode::New {
+                Err::<(), _>(FileError::Exists(RUN_YAML)).exit();
             }
-            // If deno.json file exists in current directory, generate a script database using deno.json tasks.
-            else if file::exists(DENO_JSON) {
-                let deno_json = file::read(DENO_JSON).exit();
 
-                let db = Database::from_deno_json(&deno_json).exit();
-                db.save().exit();
-            }
-            // If no file above exists in current directory, generate a script database using example.
-            else {
-                let db = Database::from_example();
-
-                db.save().exit();
+            // The scripts of every manifest in current directory are imported, or the example if there is none.
+            let manifests = read_manifests().exit();
+
+            // A template is used if it's chosen, or if the project is detected without a manifest.
+            let template = flags.template(!manifests.is_empty()).exit();
+
+            let run_yaml = match mode {
+                InitMode::Merge if existed => Some(file::read(RUN_YAML).exit()),
+                _ => None,
+            };
+
+            let (db, summary) = Database::from_manifests(
+                &manifests,
+                run_yaml.as_deref(),
+                template.as_ref().map(|(name, file_name, template)| {
+                    (*name, file_name.as_str(), template.as_ref())
+                }),
+                prompt::is_interactive(),
+            )
+            .exit();
+
+            match existed {
+                true => db.overwrite().exit(),
+                false => db.save().exit(),
             }
 
             let end_time = start_time.elapsed();
 
             println!(
-                "{} {}\n\n{}",
+                "{} {}\n\n{}\n{} {}",
                 "run.yaml".green(),
-                "is generated".yellow(),
-                format!("{} {}", "in".green(), format!("{:.2?}", end_time).yellow())
+                changed_message(mode, existed).yellow(),
+                summary,
+                "in".green(),
+                format!("{:.2?}", end_time).yellow()
             );
         }
 
         // If help flag is set, print a help message.
         Some("-h" | "--help") => {
-            println!(
-                "{}\n{}\n\n{}\n    {}\n\n{}\n    {}  {}\n    {} {}",
+            let mut content = format!(
+                "{}\n{}\n\n{}\n",
                 "Run 0.1.0".yellow(),
                 "A tool to manage end execute your scripts.".green(),
-                "Usage:".green(),
-                "run <SCRIPT NAME>".yellow(),
-                "Flags:".green(),
-                "--help, -h".yellow(),
-                "Displays a help message.".green(),
-                "--init, -i".yellow(),
-                "Creates a run.yaml file.".green(),
+                "Usage:".green()
             );
+
+            for (usage, description) in USAGES {
+                content += &format!(
+                    "    {}  {}\n",
+                    format!("{:<36}", usage).yellow(),
+                    description.green()
+                );
+            }
+
+            content += &format!("\n{}\n", "Flags:".green());
+
+            for (flag, description) in FLAGS {
+                content += &format!(
+                    "    {}  {}\n",
+                    format!("{:<22}", flag).yellow(),
+                    description.green()
+                );
+            }
+
+            print!("{}", content);
         }
 
-        // If an alias or name is given, run the script associated with it.
+        // If an alias or name is given, run the script associated with it, or the command of run with that name.
         Some(alias_or_name) => {
-            let run_yaml = file::read(RUN_YAML).exit();
+            // Without run.yaml, the scripts of the manifests are used.
+            let sources = read_sources().exit();
+
+            let db = Database::from_sources(&sources).exit();
+
+            // Scripts come before the commands of run, so the scripts with the same names keep working.
+            let exit_code = match alias_or_name {
+                name if db.contains(name) => db.run(name, args.rest(), &args).exit(),
+
+                "up" => db.up(args.rest()).map(|()| 0).exit(),
+                "down" => db.down(args.rest()).map(|()| 0).exit(),
+                "restart" => db.restart(args.rest()).map(|()| 0).exit(),
+                "ps" => db.ps().map(|()| 0).exit(),
+                "logs" => db.logs(args.rest()).map(|()| 0).exit(),
+                "history" => db.history(args.rest()).map(|()| 0).exit(),
+                "last" | "!!" => db.last(&args).exit(),
+                "bench" => db.bench(args.rest(), &args).map(|()| 0).exit(),
+                "doctor" => db.doctor().map(|()| 0).exit(),
 
-            let db = Database::from_run_yaml(&run_yaml).exit();
+                _ => db.run(alias_or_name, args.rest(), &args).exit(),
+            };
 
-            let exit_code = db.run(alias_or_name).exit();
+            trace::save().exit();
+            report::save().exit();
 
             std::process::exit(exit_code);
         }
 
         // If no arg is given, print all the available scripts.
         None => {
-            let run_yaml = file::read(RUN_YAML).exit();
+            let sources = read_sources().exit();
 
-            let database = Database::from_run_yaml(&run_yaml).exit();
+            let database = Database::from_sources(&sources).exit();
 
             database.print();
         }
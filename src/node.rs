@@ -0,0 +1,168 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::json;
+
+/// The package managers of Node.js, which are detected by their lockfiles or the `packageManager` field of `package.json`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PackageManager {
+    Npm,
+    Yarn,
+    Pnpm,
+    Bun,
+}
+
+impl PackageManager {
+    /// Returns the name of the package manager, which is also its command.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Yarn => "yarn",
+            Self::Pnpm => "pnpm",
+            Self::Bun => "bun",
+        }
+    }
+}
+
+/// The lockfiles of the package managers.
+const LOCKFILES: [(&str, PackageManager); 6] = [
+    ("package-lock.json", PackageManager::Npm),
+    ("npm-shrinkwrap.json", PackageManager::Npm),
+    ("yarn.lock", PackageManager::Yarn),
+    ("pnpm-lock.yaml", PackageManager::Pnpm),
+    ("bun.lockb", PackageManager::Bun),
+    ("bun.lock", PackageManager::Bun),
+];
+
+/// Returns the current directory and its parents up to the root of the project, nearest first.
+fn ancestors() -> Vec<PathBuf> {
+    env::current_dir()
+        .map(|dir| ancestors_of(&dir))
+        .unwrap_or_default()
+}
+
+/// Returns the directory and its parents up to the root of the project, nearest first.
+///
+/// The root is the first one which is the root of a workspace or a repository, so the other projects aren't looked at.
+fn ancestors_of(dir: &Path) -> Vec<PathBuf> {
+    let mut ancestors = vec![];
+
+    for dir in dir.ancestors() {
+        ancestors.push(dir.to_path_buf());
+
+        if is_root(dir) {
+            break;
+        }
+    }
+
+    ancestors
+}
+
+/// Returns `true` if the directory is the root of a workspace or a repository.
+fn is_root(dir: &Path) -> bool {
+    let is_workspace = || {
+        fs::read_to_string(dir.join("package.json")).is_ok_and(|package_json| {
+            json::parse(&package_json).is_ok_and(|package| package.get("workspaces").is_some())
+        })
+    };
+
+    dir.join(".git").exists() || dir.join("pnpm-workspace.yaml").is_file() || is_workspace()
+}
+
+/// Returns the `node_modules/.bin` directories which may be in the current directory and its parents, nearest first.
+///
+/// The ones in the parents have the executables hoisted to the root of a workspace.
+pub fn bin_candidates() -> Vec<PathBuf> {
+    ancestors()
+        .into_iter()
+        .map(|dir| dir.join("node_modules").join(".bin"))
+        .collect()
+}
+
+/// Returns the package manager of the project, with its version if it's given.
+///
+/// In the current directory and its parents, the `packageManager` field of `package.json` is preferred over the lockfiles.
+pub fn package_manager() -> Option<(PackageManager, Option<String>)> {
+    for dir in ancestors() {
+        let package_manager =
+            fs::read_to_string(dir.join("package.json"))
+                .ok()
+                .and_then(|package_json| {
+                    let package = json::parse(&package_json).ok()?;
+                    let (field, _) = package.get("packageManager")?;
+                    parse_package_manager(field.as_str()?)
+                });
+
+        if package_manager.is_some() {
+            return package_manager;
+        }
+
+        for (lockfile, package_manager) in LOCKFILES {
+            if dir.join(lockfile).is_file() {
+                return Some((package_manager, None));
+            }
+        }
+    }
+
+    None
+}
+
+/// Parses the `packageManager` field of `package.json`, like `pnpm@8.6.0+sha256.1234`.
+fn parse_package_manager(field: &str) -> Option<(PackageManager, Option<String>)> {
+    let (name, version) = match field.split_once('@') {
+        Some((name, version)) => (name, version.split('+').next()),
+        None => (field, None),
+    };
+
+    let package_manager = match name.trim() {
+        "npm" => PackageManager::Npm,
+        "yarn" => PackageManager::Yarn,
+        "pnpm" => PackageManager::Pnpm,
+        "bun" => PackageManager::Bun,
+        _ => return None,
+    };
+
+    let version = version
+        .map(str::trim)
+        .filter(|version| !version.is_empty())
+        .map(str::to_string);
+
+    Some((package_manager, version))
+}
+
+#[test]
+fn test() {
+    assert_eq!(
+        parse_package_manager("pnpm@8.6.0+sha256.1234"),
+        Some((PackageManager::Pnpm, Some("8.6.0".to_string())))
+    );
+    assert_eq!(
+        parse_package_manager("yarn@4.0.2"),
+        Some((PackageManager::Yarn, Some("4.0.2".to_string())))
+    );
+    assert_eq!(
+        parse_package_manager("bun"),
+        Some((PackageManager::Bun, None))
+    );
+    assert_eq!(parse_package_manager("deno@1.40.0"), None);
+
+    // The parents are only looked at up to the root of the workspace.
+    let root = env::temp_dir().join(format!("run-node-test-{}", std::process::id()));
+    let package = root.join("packages").join("app");
+
+    fs::create_dir_all(&package).unwrap();
+    fs::write(
+        root.join("package.json"),
+        r#"{ "workspaces": ["packages/*"] }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        ancestors_of(&package),
+        [package.clone(), root.join("packages"), root.clone()]
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,118 @@
+use std::{
+    env,
+    io::{stderr, IsTerminal, Write},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use crate::ci;
+
+/// The time a script must take to notify when it's finished, unless `notify_after` is given.
+pub const DEFAULT_NOTIFY_AFTER: Duration = Duration::from_secs(10);
+
+/// Whether the title of the terminal is saved, so it can be restored after the run.
+static TITLE_SAVED: AtomicBool = AtomicBool::new(false);
+
+/// Returns `true` if the terminal can be notified, which means stderr is a terminal outside of CI.
+pub fn is_enabled() -> bool {
+    stderr().is_terminal() && !ci::is_ci()
+}
+
+/// Sets the title of the terminal window and tab, with `OSC 0` and `OSC 2`.
+///
+/// The title before the first one is saved, so it's restored by `restore_title`.
+pub fn set_title(title: &str) {
+    if !is_enabled() {
+        return;
+    }
+
+    if !TITLE_SAVED.swap(true, Ordering::Relaxed) {
+        write("\x1B[22;0t");
+    }
+
+    let title = sanitize(title);
+
+    write(&format!("\x1B]0;{}\x07\x1B]2;{}\x07", title, title));
+}
+
+/// Restores the title of the terminal saved by `set_title`, if it's changed.
+pub fn restore_title() {
+    if TITLE_SAVED.swap(false, Ordering::Relaxed) {
+        write("\x1B[23;0t");
+    }
+}
+
+/// Notifies the user with the terminal bell, and with a notification in the terminals known to support `OSC 9` or `OSC 777`.
+///
+/// Only one of them is sent, as the others may display them differently, like Windows Terminal and ConEmu using `OSC 9` for other things.
+pub fn notify(title: &str, body: &str) {
+    if !is_enabled() {
+        return;
+    }
+
+    let (title, body) = (sanitize(title), sanitize(body));
+
+    let notification = match Osc::detect() {
+        Some(Osc::Nine) => format!("\x1B]9;{}: {}\x07", title, body),
+        Some(Osc::SevenSevenSeven) => format!("\x1B]777;notify;{};{}\x07", title, body),
+        None => String::new(),
+    };
+
+    write(&format!("\x07{}", notification));
+}
+
+/// The escape sequence displaying a notification in the terminal.
+enum Osc {
+    /// `OSC 9`, supported by iTerm2, WezTerm, and Ghostty.
+    Nine,
+    /// `OSC 777`, supported by the terminals based on VTE, foot, and urxvt.
+    SevenSevenSeven,
+}
+
+impl Osc {
+    /// Returns the sequence the terminal is known to display as a notification, if any.
+    fn detect() -> Option<Osc> {
+        let var = |name: &str| env::var(name).unwrap_or_default();
+
+        let term = var("TERM");
+
+        if matches!(
+            var("TERM_PROGRAM").as_str(),
+            "iTerm.app" | "WezTerm" | "ghostty"
+        ) {
+            Some(Osc::Nine)
+        } else if !var("VTE_VERSION").is_empty()
+            || term.starts_with("foot")
+            || term.starts_with("rxvt")
+        {
+            Some(Osc::SevenSevenSeven)
+        } else {
+            None
+        }
+    }
+}
+
+/// Removes the control characters, which would end the escape sequence, and `;`, which separates its fields.
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .filter(|ch| !ch.is_control() && *ch != ';')
+        .collect()
+}
+
+/// Writes the escape sequences to the terminal.
+fn write(sequences: &str) {
+    // We don't need to be warned, cuz the notifications are only hints.
+    #[allow(unused_must_use)]
+    {
+        let mut stderr = stderr().lock();
+        stderr.write_all(sequences.as_bytes());
+        stderr.flush();
+    }
+}
+
+#[test]
+fn test() {
+    // The payload must not be able to end the sequence, or to add fields to it.
+    assert_eq!(sanitize("build\x07\x1B]0;x;y\n"), "build]0xy");
+    assert_eq!(sanitize("done in 12.00s"), "done in 12.00s");
+}
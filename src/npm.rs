@@ -0,0 +1,162 @@
+use std::{env, fs, path::PathBuf, sync::OnceLock};
+
+use crate::{json, node};
+
+/// The file of the package, whose name and version are passed to the scripts.
+const PACKAGE_JSON: &str = "package.json";
+
+/// The configuration file of npm, in the current directory or in the home directory.
+const NPMRC: &str = ".npmrc";
+
+/// Returns the environment variables npm sets for the script with the given name and command.
+///
+/// They are `npm_lifecycle_event`, `npm_lifecycle_script`, `npm_package_name`, `npm_package_version`,
+/// `npm_package_json`, `npm_config_user_agent` for the package manager of the project,
+/// and `npm_config_*` for the settings in the `.npmrc` files.
+pub fn env(name: &str, command: &str) -> Vec<(String, String)> {
+    static PACKAGE_ENV: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+    let mut vars = vec![
+        ("npm_lifecycle_event".to_string(), name.to_string()),
+        ("npm_lifecycle_script".to_string(), command.to_string()),
+    ];
+
+    vars.extend(PACKAGE_ENV.get_or_init(package_env).iter().cloned());
+
+    vars
+}
+
+/// Returns the environment variables which are the same for every script of the package.
+fn package_env() -> Vec<(String, String)> {
+    let mut vars = vec![];
+
+    if let Ok(package_json) = fs::read_to_string(PACKAGE_JSON) {
+        if let Ok(package) = json::parse(&package_json) {
+            for (key, var) in [
+                ("name", "npm_package_name"),
+                ("version", "npm_package_version"),
+            ] {
+                if let Some(value) = package.get(key).and_then(|(value, _)| value.as_str()) {
+                    vars.push((var.to_string(), value.to_string()));
+                }
+            }
+        }
+
+        if let Ok(path) = fs::canonicalize(PACKAGE_JSON) {
+            vars.push(("npm_package_json".to_string(), path.display().to_string()));
+        }
+    }
+
+    // The tools find out the package manager running them from its user agent, like `pnpm/8.6.0`.
+    if let Some((package_manager, version)) = node::package_manager() {
+        if env::var_os("npm_config_user_agent").is_none() {
+            let user_agent = match version {
+                Some(version) => format!("{}/{}", package_manager.name(), version),
+                None => package_manager.name().to_string(),
+            };
+
+            vars.push(("npm_config_user_agent".to_string(), user_agent));
+        }
+    }
+
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"));
+
+    // The settings of the user are overridden by the ones of the project.
+    let npmrcs = home
+        .map(|home| PathBuf::from(home).join(NPMRC))
+        .into_iter()
+        .chain([PathBuf::from(NPMRC)]);
+
+    for npmrc in npmrcs {
+        let Ok(npmrc) = fs::read_to_string(npmrc) else {
+            continue;
+        };
+
+        for (key, value) in parse_npmrc(&npmrc) {
+            let var = config_var(key);
+
+            // The environment overrides the files, as it does in npm.
+            if env::var_os(&var).is_some() {
+                continue;
+            }
+
+            vars.retain(|(name, _)| *name != var);
+            vars.push((var, value.to_string()));
+        }
+    }
+
+    vars
+}
+
+/// Returns the settings in an `.npmrc` file, which are written like `key=value`.
+///
+/// The settings of the registries, like `//registry.npmjs.org/:_authToken`, and the credentials, like `_auth`,
+/// are skipped not to leak them.
+fn parse_npmrc(npmrc: &str) -> impl Iterator<Item = (&str, &str)> {
+    npmrc
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(['#', ';', '[']))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), unquote(value.trim())))
+        .filter(|(key, _)| !key.is_empty() && !key.starts_with("//") && !key.contains(':'))
+        .filter(|(key, _)| !is_credential(key))
+}
+
+/// Returns `true` if the setting is a credential, like `_auth`, `_password`, or `email-token`.
+fn is_credential(key: &str) -> bool {
+    let key = key.to_lowercase();
+
+    key.starts_with('_')
+        || ["auth", "token", "password", "secret"]
+            .iter()
+            .any(|word| key.contains(word))
+}
+
+/// Removes the quotes around the value, if any.
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(value) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return value;
+        }
+    }
+
+    value
+}
+
+/// Returns the name of the environment variable for the setting, like `npm_config_save_exact` for `save-exact`.
+fn config_var(key: &str) -> String {
+    format!("npm_config_{}", key.to_lowercase().replace('-', "_"))
+}
+
+#[test]
+fn test() {
+    let npmrc = r#"
+        # The settings of the project.
+        save-exact=true
+        registry = "https://registry.example.com/"
+        //registry.npmjs.org/:_authToken=secret
+        @scope:registry=https://npm.example.com/
+        _auth=c2VjcmV0
+        _password = secret
+        npm-token=secret
+        always-auth=true
+        [section]
+        ; A comment.
+    "#;
+
+    let settings: Vec<_> = parse_npmrc(npmrc).collect();
+
+    assert_eq!(
+        settings,
+        [
+            ("save-exact", "true"),
+            ("registry", "https://registry.example.com/")
+        ]
+    );
+
+    assert_eq!(config_var("Save-Exact"), "npm_config_save_exact");
+}
@@ -0,0 +1,183 @@
+use std::{
+    io::{stderr, stdout, IsTerminal, Read, Result, Write},
+    process::{Command, ExitStatus, Stdio},
+    sync::Mutex,
+    thread,
+};
+
+use crate::{
+    progress,
+    secrets::Masker,
+    usage::{self, Usage},
+};
+
+/// The output streams of a process.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// The trait for the things receiving the output of a script, line by line.
+pub trait Sink: Send {
+    /// Receives a line of the output, without the line break.
+    fn line(&mut self, stream: Stream, line: &str);
+}
+
+/// Executes `command`, then returns its exit status and the resources it used, if they are known.
+///
+/// The output is written to the terminal as it comes, and passed to the sinks line by line.
+/// If there is a masker, the secrets are masked before both.
+pub fn capture(
+    command: &mut Command,
+    masker: Option<&Masker>,
+    sinks: &mut [&mut dyn Sink],
+) -> Result<(ExitStatus, Option<Usage>)> {
+    // The output is piped, so let the programs know they can still use colors.
+    if stdout().is_terminal() {
+        command.env("FORCE_COLOR", "1").env("CLICOLOR_FORCE", "1");
+    }
+
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let child_stdout = child.stdout.take();
+    let child_stderr = child.stderr.take();
+
+    let sinks = Mutex::new(sinks);
+
+    thread::scope(|scope| {
+        if let Some(child_stdout) = child_stdout {
+            scope.spawn(|| forward(child_stdout, Stream::Stdout, masker, &sinks));
+        }
+
+        if let Some(child_stderr) = child_stderr {
+            scope.spawn(|| forward(child_stderr, Stream::Stderr, masker, &sinks));
+        }
+    });
+
+    usage::wait(&mut child)
+}
+
+/// Writes the output read from `reader` to the terminal, and passes its lines to the sinks.
+fn forward(
+    mut reader: impl Read,
+    stream: Stream,
+    masker: Option<&Masker>,
+    sinks: &Mutex<&mut [&mut dyn Sink]>,
+) {
+    let mut buf = [0; 8192];
+    let mut pending = vec![];
+    let mut line = vec![];
+
+    loop {
+        let (len, end) = match reader.read(&mut buf) {
+            Ok(0) | Err(_) => (0, true),
+            Ok(len) => (len, false),
+        };
+
+        pending.extend_from_slice(&buf[..len]);
+
+        // The end of the output may be kept by the masker, until it's known not to be a secret.
+        let output = match masker {
+            Some(masker) => masker.mask(&mut pending, end),
+            None => std::mem::take(&mut pending),
+        };
+
+        // The line of the progress is removed before the output, if it's shown.
+        progress::write_output(&output, |output| {
+            // We don't need to be warned, cuz the output is also passed to the sinks.
+            #[allow(unused_must_use)]
+            match stream {
+                Stream::Stdout => {
+                    let mut stdout = stdout().lock();
+                    stdout.write_all(output);
+                    stdout.flush();
+                }
+                Stream::Stderr => {
+                    stderr().write_all(output);
+                }
+            }
+        });
+
+        line.extend_from_slice(&output);
+
+        // Pass the complete lines to the sinks.
+        while let Some(index) = line.iter().position(|byte| *byte == b'\n') {
+            let rest = line.split_off(index + 1);
+
+            pass(&line, stream, sinks);
+
+            line = rest;
+        }
+
+        if end {
+            break;
+        }
+    }
+
+    // Pass the last line, even if it doesn't end with a line break.
+    if !line.is_empty() {
+        pass(&line, stream, sinks);
+    }
+}
+
+/// Passes the line to the sinks, after removing its line break.
+fn pass(line: &[u8], stream: Stream, sinks: &Mutex<&mut [&mut dyn Sink]>) {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end_matches(['\n', '\r']);
+
+    if let Ok(mut sinks) = sinks.lock() {
+        for sink in sinks.iter_mut() {
+            sink.line(stream, line);
+        }
+    }
+}
+
+/// Removes the ANSI escape sequences, like colors, from the text.
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\x1B' {
+            stripped.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            // Control sequences end with a character between `@` and `~`, like `\x1B[1;32m`.
+            Some('[') => {
+                for ch in chars.by_ref() {
+                    if ('@'..='~').contains(&ch) {
+                        break;
+                    }
+                }
+            }
+
+            // Operating system commands end with a bell or `\x1B\\`, like `\x1B]0;title\x07`.
+            Some(']') => {
+                while let Some(ch) = chars.next() {
+                    if ch == '\x07' || (ch == '\x1B' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+
+            // Other escape sequences are two characters long.
+            _ => (),
+        }
+    }
+
+    stripped
+}
+
+#[test]
+fn test() {
+    assert_eq!(strip_ansi("\x1B[1;32mrun\x1B[0m build"), "run build");
+    assert_eq!(strip_ansi("\x1B]0;title\x07done"), "done");
+    assert_eq!(strip_ansi("\x1B]8;;http://x\x1B\\link"), "link");
+    assert_eq!(strip_ansi("plain"), "plain");
+}
@@ -0,0 +1,225 @@
+use std::{
+    env,
+    io::{stderr, IsTerminal, Write},
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{beautify::Beautify, ci, service::format_secs};
+
+/// The time between the updates of the progress.
+const INTERVAL: Duration = Duration::from_millis(500);
+
+/// The state of the line of the progress, which is shown below the output of the script.
+struct Line {
+    /// Whether the line is shown now.
+    shown: bool,
+    /// Whether the output of the script ends with a line break, so the line can be shown below it.
+    at_line_start: bool,
+}
+
+/// The line of the progress, which is also held while the output of the script is written.
+static LINE: Mutex<Line> = Mutex::new(Line {
+    shown: false,
+    at_line_start: true,
+});
+
+/// A progress indicator in the terminal, estimated from the typical duration of a script.
+///
+/// It's shown with `OSC 9;4` in the terminals known to display it as a progress bar, like Windows Terminal.
+/// If the output of the script already goes through run, it's also shown as a line like `build 12s, about 9s left`
+/// below the output, which is removed before the output is written.
+pub struct Progress {
+    /// The sender to stop the updates.
+    stop: Option<Sender<()>>,
+    /// The thread updating the progress.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Progress {
+    /// Starts showing the progress of the script, if stderr is a terminal outside of CI.
+    ///
+    /// The line is only shown if `captured` is `true`, which means the output of the script goes through run.
+    pub fn start(name: &str, expected: Duration, captured: bool) -> Option<Progress> {
+        let has_bar = has_progress_bar();
+
+        if !stderr().is_terminal() || ci::is_ci() || expected.is_zero() || !(captured || has_bar) {
+            return None;
+        }
+
+        let name = name.to_string();
+        let (stop, receiver) = mpsc::channel();
+
+        let thread = thread::spawn(move || {
+            let start_time = Instant::now();
+
+            // The first update comes after an interval, not to show the progress of a script finishing at once.
+            while let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(INTERVAL) {
+                let elapsed = start_time.elapsed();
+
+                // It never reaches 100%, cuz the script may take longer than usual.
+                let percent =
+                    (elapsed.as_secs_f64() / expected.as_secs_f64() * 100.0).min(99.0) as u32;
+
+                if has_bar {
+                    set(&format!("1;{}", percent));
+                }
+
+                if captured {
+                    show(&format!(
+                        "{} {}",
+                        name.yellow(),
+                        estimate(elapsed, expected).green()
+                    ));
+                }
+            }
+
+            hide();
+
+            if has_bar {
+                set("0;0");
+            }
+        });
+
+        Some(Progress {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+// Implement `Drop` trait for `Progress`, removing the progress from the terminal.
+impl Drop for Progress {
+    fn drop(&mut self) {
+        // Dropping the sender stops the updates.
+        self.stop.take();
+
+        if let Some(thread) = self.thread.take() {
+            // We don't need to be warned, cuz the thread only writes to the terminal.
+            #[allow(unused_must_use)]
+            {
+                thread.join();
+            }
+        }
+    }
+}
+
+/// Writes the output of the script with `write`, removing the line of the progress before it, if it's shown.
+///
+/// The line is shown again with the next update, if the output ends with a line break.
+pub fn write_output(output: &[u8], write: impl FnOnce(&[u8])) {
+    let mut line = LINE.lock().unwrap_or_else(|err| err.into_inner());
+
+    if line.shown && !output.is_empty() {
+        erase();
+        line.shown = false;
+    }
+
+    write(output);
+
+    if let Some(last) = output.last() {
+        line.at_line_start = *last == b'\n';
+    }
+}
+
+/// Returns the estimate of the progress, like `12s, about 9s left`, or `25s, usually 21s` if it takes longer.
+fn estimate(elapsed: Duration, expected: Duration) -> String {
+    let (elapsed, expected) = (elapsed.as_secs(), expected.as_secs_f64().round() as u64);
+
+    match expected.checked_sub(elapsed) {
+        Some(left) if left > 0 => {
+            format!("{}, about {} left", format_secs(elapsed), format_secs(left))
+        }
+        _ => format!(
+            "{}, usually {}",
+            format_secs(elapsed),
+            format_secs(expected)
+        ),
+    }
+}
+
+/// Shows the line of the progress with the given text, unless the output of the script is in the middle of a line.
+fn show(text: &str) {
+    let mut line = LINE.lock().unwrap_or_else(|err| err.into_inner());
+
+    if !line.at_line_start {
+        return;
+    }
+
+    // We don't need to be warned, cuz the progress is only a hint.
+    #[allow(unused_must_use)]
+    {
+        let mut stderr = stderr().lock();
+        write!(stderr, "\r\x1B[2K{}", text);
+        stderr.flush();
+    }
+
+    line.shown = true;
+}
+
+/// Removes the line of the progress, if it's shown.
+fn hide() {
+    let mut line = LINE.lock().unwrap_or_else(|err| err.into_inner());
+
+    if line.shown {
+        erase();
+        line.shown = false;
+    }
+
+    line.at_line_start = true;
+}
+
+/// Erases the current line of the terminal.
+fn erase() {
+    // We don't need to be warned, cuz the progress is only a hint.
+    #[allow(unused_must_use)]
+    {
+        let mut stderr = stderr().lock();
+        write!(stderr, "\r\x1B[2K");
+        stderr.flush();
+    }
+}
+
+/// Returns `true` if the terminal is known to display `OSC 9;4` as a progress bar.
+///
+/// The others may display it differently, like the older versions of iTerm2 showing `OSC 9` as a notification.
+fn has_progress_bar() -> bool {
+    let var = |name: &str| env::var(name).unwrap_or_default();
+
+    // iTerm2 displays it as a progress bar since 3.6.
+    let is_new_iterm = || {
+        let version = var("TERM_PROGRAM_VERSION");
+        let mut parts = version.split('.').map(|part| part.parse().unwrap_or(0));
+
+        (parts.next().unwrap_or(0), parts.next().unwrap_or(0)) >= (3, 6)
+    };
+
+    !var("WT_SESSION").is_empty()
+        || !var("ConEmuPID").is_empty()
+        || matches!(var("TERM_PROGRAM").as_str(), "ghostty" | "WezTerm")
+        || (var("TERM_PROGRAM") == "iTerm.app" && is_new_iterm())
+}
+
+/// Sets the state and the value of the progress bar in the terminal.
+fn set(state: &str) {
+    // We don't need to be warned, cuz the progress is only a hint.
+    #[allow(unused_must_use)]
+    {
+        let mut stderr = stderr().lock();
+        write!(stderr, "\x1B]9;4;{}\x07", state);
+        stderr.flush();
+    }
+}
+
+#[test]
+fn test() {
+    let secs = Duration::from_secs;
+
+    assert_eq!(estimate(secs(12), secs(21)), "12s, about 9s left");
+    assert_eq!(estimate(secs(25), secs(21)), "25s, usually 21s");
+    assert_eq!(estimate(secs(61), secs(125)), "1m 1s, about 1m 4s left");
+}
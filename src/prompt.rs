@@ -0,0 +1,35 @@
+use std::{
+    io::{stdin, stdout, IsTerminal, Write},
+    sync::Mutex,
+};
+
+use crate::{beautify::Beautify, ci};
+
+/// Held while a question is asked, so the questions of the scripts run at the same time aren't mixed.
+static ASKING: Mutex<()> = Mutex::new(());
+
+/// Returns `true` if questions can be asked, which means standard input is a terminal outside of CI.
+pub fn is_interactive() -> bool {
+    stdin().is_terminal() && !ci::is_ci()
+}
+
+/// Asks the question of the script with the given name, then returns `true` if it's answered with yes.
+pub fn confirm(name: &str, question: &str) -> bool {
+    ask(name, question, "[y/N]").is_some_and(|answer| matches!(answer.as_str(), "y" | "yes"))
+}
+
+/// Asks the question about the given name, with the hint of the answers, then returns the answer in lowercase.
+pub fn ask(name: &str, question: &str, hint: &str) -> Option<String> {
+    let _asking = ASKING.lock();
+
+    print!("{} {} {} ", name.yellow(), question.green(), hint.yellow());
+
+    // The question must be displayed before reading the answer.
+    stdout().flush().ok()?;
+
+    let mut answer = String::new();
+
+    stdin().read_line(&mut answer).ok()?;
+
+    Some(answer.trim().to_lowercase())
+}
@@ -0,0 +1,262 @@
+use std::{
+    fmt::Display,
+    fs,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    beautify::Beautify,
+    regex::Regex,
+    service::{self, Status},
+};
+
+/// The default time to wait for a condition.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A condition to be waited for.
+pub enum Condition<'a> {
+    /// A local TCP port accepts connections, written as `port 3000`.
+    Port(u16),
+    /// A file exists, written as `file tmp/ready`.
+    File(&'a str),
+    /// A local HTTP endpoint responds with 2xx, written as `http://localhost:3000/health`.
+    Http(&'a str),
+    /// A line of the output of the service matches, written as `output <regex>` or only as `<regex>`.
+    Output(Regex),
+}
+
+impl<'a> Condition<'a> {
+    /// Parses the given condition.
+    ///
+    /// A condition without a known prefix is a regex for the output, but the other URLs, like `https://`, are rejected.
+    /// Returns `None` if the condition is invalid.
+    pub fn parse(condition: &'a str) -> Option<Condition<'a>> {
+        if let Some(port) = condition.strip_prefix("port ") {
+            port.trim().parse().ok().map(Condition::Port)
+        } else if let Some(file) = condition.strip_prefix("file ") {
+            Some(Condition::File(file.trim())).filter(|_| !file.trim().is_empty())
+        } else if condition.starts_with("http://") {
+            split_url(condition).map(|_| Condition::Http(condition))
+        } else if let Some(regex) = condition.strip_prefix("output ") {
+            Regex::new(regex).map(Condition::Output)
+        } else if condition.contains("://") {
+            None
+        } else {
+            Regex::new(condition).map(Condition::Output)
+        }
+    }
+
+    /// Returns `true` if the condition holds.
+    ///
+    /// The output of the service with the given name is checked for `Condition::Output`.
+    fn holds(&self, name: &str) -> bool {
+        match self {
+            Self::Port(port) => ("localhost", *port)
+                .to_socket_addrs()
+                .map(|mut addrs| {
+                    addrs.any(|addr| {
+                        TcpStream::connect_timeout(&addr, Duration::from_secs(1)).is_ok()
+                    })
+                })
+                .unwrap_or(false),
+
+            Self::File(file) => Path::new(file).exists(),
+
+            Self::Http(url) => is_http_ok(url).unwrap_or(false),
+
+            Self::Output(regex) => fs::read(service::log_path(name))
+                .map(|log| {
+                    String::from_utf8_lossy(&log)
+                        .lines()
+                        .any(|line| regex.is_match(line))
+                })
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// The error type for waiting operations.
+pub enum ReadyError {
+    /// The service or the condition isn't ready in time.
+    Timeout(String, Duration),
+    /// The service exits before being ready.
+    Exited(String),
+    /// The condition isn't valid.
+    BadCondition(String),
+}
+
+// Implement `Display` trait for `ReadyError`.
+impl Display for ReadyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout(name, timeout) => write!(
+                f,
+                "{} {} {}",
+                name.green(),
+                "isn't ready in".red(),
+                format!("{:?}", timeout).yellow()
+            ),
+            Self::Exited(name) => write!(
+                f,
+                "{} {}\n\n{}",
+                name.green(),
+                "exited before being ready".red(),
+                format!("type `run logs {}` to see why", name).yellow()
+            ),
+            Self::BadCondition(condition) => write!(
+                f,
+                "{} {}\n\n{}",
+                condition.green(),
+                "isn't a valid condition".red(),
+                "use `port <PORT>`, `file <PATH>`, `http://<URL>`, or `output <REGEX>`".yellow()
+            ),
+        }
+    }
+}
+
+/// Waits until the given condition holds, or the timeout is reached.
+///
+/// If a service name is given, the condition is about that service, and waiting stops when it exits.
+pub fn wait(condition: &str, service: Option<&str>, timeout: Duration) -> Result<(), ReadyError> {
+    let start_time = Instant::now();
+
+    // The name to be displayed in the errors.
+    let name = service.unwrap_or(condition);
+
+    let Some(parsed) = Condition::parse(condition) else {
+        return Err(ReadyError::BadCondition(condition.to_string()));
+    };
+
+    while !parsed.holds(service.unwrap_or_default()) {
+        if let Some(service) = service {
+            if let Status::Stopped(_) = service::status(service) {
+                return Err(ReadyError::Exited(service.to_string()));
+            }
+        }
+
+        if start_time.elapsed() > timeout {
+            return Err(ReadyError::Timeout(name.to_string(), timeout));
+        }
+
+        thread::sleep(Duration::from_millis(250));
+    }
+
+    Ok(())
+}
+
+/// Parses a duration like `500ms`, `30s`, `2m`, or `30` which means seconds.
+pub fn parse_duration(duration: &str) -> Option<Duration> {
+    let (number, unit) = if let Some(millis) = duration.strip_suffix("ms") {
+        (millis, 0.001)
+    } else if let Some(secs) = duration.strip_suffix('s') {
+        (secs, 1.0)
+    } else if let Some(mins) = duration.strip_suffix('m') {
+        (mins, 60.0)
+    } else {
+        (duration, 1.0)
+    };
+
+    let secs = number.trim().parse::<f64>().ok()? * unit;
+
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+/// Splits an HTTP URL into its host with port and its path.
+fn split_url(url: &str) -> Option<(String, &str)> {
+    let rest = url.strip_prefix("http://")?;
+
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+
+    if host.is_empty() {
+        return None;
+    }
+
+    // Use the default port, if none is given.
+    match host.contains(':') {
+        true => Some((host.to_string(), path)),
+        false => Some((format!("{}:80", host), path)),
+    }
+}
+
+/// Sends a GET request to the given URL, then returns `true` if the response status is 2xx.
+fn is_http_ok(url: &str) -> Option<bool> {
+    let (host, path) = split_url(url)?;
+
+    let addr = host.to_socket_addrs().ok()?.next()?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, Duration::from_secs(1)).ok()?;
+
+    stream.set_read_timeout(Some(Duration::from_secs(1))).ok()?;
+
+    write!(
+        stream,
+        "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, host
+    )
+    .ok()?;
+
+    // Only the status line is needed, like `HTTP/1.1 200 OK`.
+    let mut buf = vec![];
+    stream.take(64).read_to_end(&mut buf).ok()?;
+    let status_line = String::from_utf8_lossy(&buf).to_string();
+
+    Some(status_line.split(' ').nth(1)?.starts_with('2'))
+}
+
+#[test]
+fn test() {
+    // Check conditions.
+    assert!(matches!(
+        Condition::parse("port 3000"),
+        Some(Condition::Port(3000))
+    ));
+    assert!(matches!(
+        Condition::parse("file tmp/ready"),
+        Some(Condition::File("tmp/ready"))
+    ));
+    assert!(matches!(
+        Condition::parse("http://localhost:3000/health"),
+        Some(Condition::Http(_))
+    ));
+    assert!(matches!(
+        Condition::parse("output listening on"),
+        Some(Condition::Output(_))
+    ));
+    assert!(Condition::parse("port http").is_none());
+    assert!(Condition::parse("http://").is_none());
+
+    // The unsupported URLs must be rejected, instead of being waited for until the timeout.
+    assert!(Condition::parse("https://localhost/health").is_none());
+
+    // The conditions without a known prefix are regexes for the output.
+    assert!(matches!(
+        Condition::parse("listening on"),
+        Some(Condition::Output(_))
+    ));
+    assert!(Condition::parse("listening (on").is_none());
+
+    // Check durations.
+    assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+    assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+    assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_duration("10"), Some(Duration::from_secs(10)));
+    assert_eq!(parse_duration("soon"), None);
+    assert_eq!(parse_duration("-5s"), None);
+
+    // Check URLs.
+    assert_eq!(
+        split_url("http://localhost:3000/health"),
+        Some(("localhost:3000".to_string(), "/health"))
+    );
+    assert_eq!(
+        split_url("http://localhost"),
+        Some(("localhost:80".to_string(), "/"))
+    );
+}
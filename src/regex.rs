@@ -0,0 +1,248 @@
+/// A single thing to be matched with a character.
+enum Atom {
+    /// Matches any character.
+    Any,
+    /// Matches the given character.
+    Char(char),
+    /// Matches a character in the given ranges, or not in them if it's negated.
+    Class(Vec<(char, char)>, bool),
+}
+
+impl Atom {
+    /// Returns `true` if the atom matches the given character.
+    fn matches(&self, ch: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Char(expected) => ch == *expected,
+            Self::Class(ranges, negated) => {
+                ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&ch))
+                    != *negated
+            }
+        }
+    }
+}
+
+/// The number of times an atom can be repeated.
+#[derive(Clone, Copy)]
+enum Repeat {
+    /// Exactly once.
+    One,
+    /// Zero or one time, written as `?`.
+    Optional,
+    /// Any times, written as `*`.
+    Any,
+    /// At least once, written as `+`.
+    Many,
+}
+
+/// A sequence of atoms, which is an alternative of a regular expression.
+struct Sequence {
+    /// Whether the sequence must match at the beginning of the text, written as `^`.
+    start: bool,
+    /// The atoms and their repetitions.
+    atoms: Vec<(Atom, Repeat)>,
+    /// Whether the sequence must match at the end of the text, written as `$`.
+    end: bool,
+}
+
+/// A small regular expression.
+///
+/// Supports `.`, `*`, `+`, `?`, `^`, `$`, `|`, classes like `[a-z]` or `[^0-9]`, and escapes like `\d`, `\w`, `\s`.
+pub struct Regex {
+    /// The alternatives separated by `|`.
+    alternatives: Vec<Sequence>,
+}
+
+impl Regex {
+    /// Parses the given pattern.
+    ///
+    /// Returns `None` if the pattern is invalid or uses an unsupported feature, like groups.
+    pub fn new(pattern: &str) -> Option<Regex> {
+        let mut alternatives = vec![];
+        let mut sequence = Sequence {
+            start: false,
+            atoms: vec![],
+            end: false,
+        };
+
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            // Nothing can come after `$`, other than another alternative.
+            if sequence.end && ch != '|' {
+                return None;
+            }
+
+            let atom = match ch {
+                '|' => {
+                    alternatives.push(sequence);
+                    sequence = Sequence {
+                        start: false,
+                        atoms: vec![],
+                        end: false,
+                    };
+                    continue;
+                }
+
+                '^' if sequence.atoms.is_empty() && !sequence.start => {
+                    sequence.start = true;
+                    continue;
+                }
+
+                '$' => {
+                    sequence.end = true;
+                    continue;
+                }
+
+                '.' => Atom::Any,
+
+                '\\' => escape(chars.next()?),
+
+                '[' => {
+                    let negated = chars.next_if_eq(&'^').is_some();
+                    let mut ranges = vec![];
+
+                    loop {
+                        let start = match chars.next()? {
+                            ']' if !ranges.is_empty() => break,
+                            '\\' => match escape(chars.next()?) {
+                                Atom::Char(ch) => ch,
+                                Atom::Class(class_ranges, false) => {
+                                    ranges.extend(class_ranges);
+                                    continue;
+                                }
+                                _ => return None,
+                            },
+                            ch => ch,
+                        };
+
+                        // A minus between two characters makes a range, unless it's the last character.
+                        if chars.peek() == Some(&'-') {
+                            chars.next();
+
+                            match chars.next()? {
+                                ']' => {
+                                    ranges.push((start, start));
+                                    ranges.push(('-', '-'));
+                                    break;
+                                }
+                                end if end >= start => ranges.push((start, end)),
+                                _ => return None,
+                            }
+                        } else {
+                            ranges.push((start, start));
+                        }
+                    }
+
+                    Atom::Class(ranges, negated)
+                }
+
+                '(' | ')' | '{' | '}' | '*' | '+' | '?' => return None,
+
+                ch => Atom::Char(ch),
+            };
+
+            let repeat = match chars.next_if(|ch| matches!(ch, '*' | '+' | '?')) {
+                Some('*') => Repeat::Any,
+                Some('+') => Repeat::Many,
+                Some('?') => Repeat::Optional,
+                _ => Repeat::One,
+            };
+
+            sequence.atoms.push((atom, repeat));
+        }
+
+        alternatives.push(sequence);
+
+        Some(Regex { alternatives })
+    }
+
+    /// Returns `true` if the regular expression matches any part of the text.
+    pub fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+
+        self.alternatives.iter().any(|sequence| {
+            if sequence.start {
+                matches_here(&sequence.atoms, &chars, sequence.end)
+            } else {
+                (0..=chars.len()).any(|i| matches_here(&sequence.atoms, &chars[i..], sequence.end))
+            }
+        })
+    }
+}
+
+/// Returns the atom for the escaped character.
+fn escape(ch: char) -> Atom {
+    let digits = vec![('0', '9')];
+    let words = vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+    let spaces = vec![(' ', ' '), ('\t', '\t'), ('\n', '\n'), ('\r', '\r')];
+
+    match ch {
+        'd' => Atom::Class(digits, false),
+        'D' => Atom::Class(digits, true),
+        'w' => Atom::Class(words, false),
+        'W' => Atom::Class(words, true),
+        's' => Atom::Class(spaces, false),
+        'S' => Atom::Class(spaces, true),
+        't' => Atom::Char('\t'),
+        ch => Atom::Char(ch),
+    }
+}
+
+/// Returns `true` if the atoms match the beginning of the text.
+///
+/// If `end` is `true`, the atoms must match the entire text.
+fn matches_here(atoms: &[(Atom, Repeat)], text: &[char], end: bool) -> bool {
+    let Some(((atom, repeat), rest)) = atoms.split_first() else {
+        return !end || text.is_empty();
+    };
+
+    let (min, max) = match repeat {
+        Repeat::One => (1, 1),
+        Repeat::Optional => (0, 1),
+        Repeat::Any => (0, usize::MAX),
+        Repeat::Many => (1, usize::MAX),
+    };
+
+    // Count how many characters the atom can match, then try the longest first.
+    let count = text
+        .iter()
+        .take(max)
+        .take_while(|ch| atom.matches(**ch))
+        .count();
+
+    (min..=count)
+        .rev()
+        .any(|taken| matches_here(rest, &text[taken..], end))
+}
+
+#[test]
+fn test() {
+    let is_match = |pattern: &str, text: &str| Regex::new(pattern).unwrap().is_match(text);
+
+    // Check literals and anchors.
+    assert!(is_match("listening on", "server is listening on :3000"));
+    assert!(!is_match("^listening", "server is listening"));
+    assert!(is_match("^ready$", "ready"));
+    assert!(!is_match("^ready$", "ready!"));
+
+    // Check repetitions and classes.
+    assert!(is_match(r"port \d+", "listening on port 8080"));
+    assert!(!is_match(r"port \d+", "listening on port x"));
+    assert!(is_match("colou?r", "color"));
+    assert!(is_match("a.*z", "a to z"));
+    assert!(is_match("[a-c]+x", "abcx"));
+    assert!(!is_match("[^a-c]x", "ax"));
+    assert!(is_match(r"v[\d.]+", "v1.2.3"));
+
+    // Check alternatives.
+    assert!(is_match("ready|listening", "now listening"));
+    assert!(!is_match("^ready|^listening", "not ready"));
+
+    // Unsupported features must be rejected.
+    assert!(Regex::new("(a|b)").is_none());
+    assert!(Regex::new("a{2}").is_none());
+    assert!(Regex::new("*a").is_none());
+}
@@ -0,0 +1,318 @@
+use std::{collections::VecDeque, fmt::Display, fs, sync::Mutex, time::Duration};
+
+use crate::{
+    beautify::Beautify,
+    json,
+    output::{self, Sink, Stream},
+};
+
+/// The number of the last lines of stderr, reported for the failed scripts.
+const TAIL_LINES: usize = 20;
+
+/// The report being recorded, if it's asked for.
+static REPORT: Mutex<Option<Report>> = Mutex::new(None);
+
+/// The formats of the reports.
+#[derive(Clone, Copy)]
+enum Format {
+    Junit,
+    Json,
+}
+
+/// A script executed during the run.
+struct Case {
+    name: String,
+    duration: Duration,
+    exit_code: i32,
+    stderr_tail: String,
+}
+
+/// A report of the scripts executed during the run, with a test case for each script.
+struct Report {
+    /// The name of the script which is run.
+    name: String,
+    /// The formats and the files which the report is saved to.
+    targets: Vec<(Format, String)>,
+    /// The scripts executed, in order.
+    cases: Vec<Case>,
+}
+
+/// The error type for the reports.
+pub enum ReportError {
+    BadTarget(String),
+    CannotBeSaved(String),
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadTarget(target) => write!(
+                f,
+                "{} {}{} {}",
+                "the report".red(),
+                target.yellow(),
+                " isn't valid,".red(),
+                "use `junit=<FILE>` or `json=<FILE>`".red()
+            ),
+            Self::CannotBeSaved(path) => write!(
+                f,
+                "{} {}",
+                "the report can't be saved to".red(),
+                path.yellow()
+            ),
+        }
+    }
+}
+
+/// Starts recording a report, to be saved to the files given like `junit=report.xml` or `json=report.json`.
+pub fn init(targets: &[String]) -> Result<(), ReportError> {
+    let mut report = Report {
+        name: "run".to_string(),
+        targets: vec![],
+        cases: vec![],
+    };
+
+    for target in targets {
+        let format = match target.split_once('=') {
+            Some(("junit", path)) if !path.is_empty() => (Format::Junit, path),
+            Some(("json", path)) if !path.is_empty() => (Format::Json, path),
+            _ => return Err(ReportError::BadTarget(target.clone())),
+        };
+
+        report.targets.push((format.0, format.1.to_string()));
+    }
+
+    if let Ok(mut current) = REPORT.lock() {
+        *current = Some(report);
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if a report is being recorded.
+pub fn enabled() -> bool {
+    REPORT.lock().is_ok_and(|report| report.is_some())
+}
+
+/// Sets the name of the report, which is the name of the script run.
+pub fn set_name(name: &str) {
+    if let Ok(mut report) = REPORT.lock() {
+        if let Some(report) = report.as_mut() {
+            report.name = name.to_string();
+        }
+    }
+}
+
+/// Adds a script executed to the report, if a report is being recorded.
+pub fn case(name: &str, duration: Duration, exit_code: i32, tail: &Tail) {
+    if let Ok(mut report) = REPORT.lock() {
+        if let Some(report) = report.as_mut() {
+            report.cases.push(Case {
+                name: name.to_string(),
+                duration,
+                exit_code,
+                stderr_tail: tail.lines.iter().cloned().collect::<Vec<_>>().join("\n"),
+            });
+        }
+    }
+}
+
+/// Saves the report to its files, if a report is being recorded.
+pub fn save() -> Result<(), ReportError> {
+    let Ok(mut report) = REPORT.lock() else {
+        return Ok(());
+    };
+
+    // The report is saved once, even if it's asked again.
+    let Some(report) = report.take() else {
+        return Ok(());
+    };
+
+    for (format, path) in &report.targets {
+        let content = match format {
+            Format::Junit => to_junit(&report),
+            Format::Json => to_json(&report),
+        };
+
+        fs::write(path, content).map_err(|_| ReportError::CannotBeSaved(path.clone()))?;
+    }
+
+    Ok(())
+}
+
+/// Converts the report to JUnit XML.
+fn to_junit(report: &Report) -> String {
+    let failures = report
+        .cases
+        .iter()
+        .filter(|case| case.exit_code != 0)
+        .count();
+    let time: f64 = report
+        .cases
+        .iter()
+        .map(|case| case.duration.as_secs_f64())
+        .sum();
+
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"run\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        report.cases.len(),
+        failures,
+        time
+    );
+
+    xml += &format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        escape_xml(&report.name),
+        report.cases.len(),
+        failures,
+        time
+    );
+
+    for case in &report.cases {
+        xml += &format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape_xml(&case.name),
+            escape_xml(&report.name),
+            case.duration.as_secs_f64()
+        );
+
+        if case.exit_code == 0 {
+            xml += " />\n";
+            continue;
+        }
+
+        xml += &format!(
+            ">\n      <failure message=\"error code {}\">{}</failure>\n    </testcase>\n",
+            case.exit_code,
+            escape_xml(&case.stderr_tail)
+        );
+    }
+
+    xml += "  </testsuite>\n</testsuites>\n";
+
+    xml
+}
+
+/// Converts the report to JSON.
+fn to_json(report: &Report) -> String {
+    let failures = report
+        .cases
+        .iter()
+        .filter(|case| case.exit_code != 0)
+        .count();
+
+    let cases: Vec<String> = report
+        .cases
+        .iter()
+        .map(|case| {
+            // The tail of stderr is only reported for the failed scripts.
+            let stderr_tail = match case.exit_code {
+                0 => "null".to_string(),
+                _ => json::string(&case.stderr_tail),
+            };
+
+            format!(
+                "    {{\"name\":{},\"duration_ms\":{},\"exit_code\":{},\"stderr_tail\":{}}}",
+                json::string(&case.name),
+                case.duration.as_millis(),
+                case.exit_code,
+                stderr_tail
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"name\": {},\n  \"tests\": {},\n  \"failures\": {},\n  \"cases\": [\n{}\n  ]\n}}\n",
+        json::string(&report.name),
+        report.cases.len(),
+        failures,
+        cases.join(",\n")
+    )
+}
+
+/// Escapes the special characters of XML, and removes the control characters XML doesn't allow.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&apos;",
+            '\n' | '\t' | '\r' => escaped.push(ch),
+            ch if ch < ' ' => (),
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// The sink which keeps the last lines of stderr, to be reported if the script fails.
+#[derive(Default)]
+pub struct Tail {
+    lines: VecDeque<String>,
+}
+
+// Implement `Sink` trait for `Tail`.
+impl Sink for Tail {
+    fn line(&mut self, stream: Stream, line: &str) {
+        if stream != Stream::Stderr {
+            return;
+        }
+
+        if self.lines.len() == TAIL_LINES {
+            self.lines.pop_front();
+        }
+
+        self.lines.push_back(output::strip_ansi(line));
+    }
+}
+
+#[test]
+fn test() {
+    let mut tail = Tail::default();
+
+    for i in 0..25 {
+        tail.line(Stream::Stderr, &format!("\x1B[31mline {}\x1B[0m", i));
+        tail.line(Stream::Stdout, "ignored");
+    }
+
+    let report = Report {
+        name: "ci".to_string(),
+        targets: vec![],
+        cases: vec![
+            Case {
+                name: "lint".to_string(),
+                duration: Duration::from_millis(1500),
+                exit_code: 0,
+                stderr_tail: String::new(),
+            },
+            Case {
+                name: "test <unit>".to_string(),
+                duration: Duration::from_millis(250),
+                exit_code: 1,
+                stderr_tail: tail.lines.iter().cloned().collect::<Vec<_>>().join("\n"),
+            },
+        ],
+    };
+
+    // Only the last lines of stderr are kept, without colors.
+    assert_eq!(tail.lines.len(), TAIL_LINES);
+    assert_eq!(tail.lines[0], "line 5");
+
+    let xml = to_junit(&report);
+
+    assert!(xml.contains("<testsuites name=\"run\" tests=\"2\" failures=\"1\" time=\"1.750\">"));
+    assert!(xml.contains("<testcase name=\"lint\" classname=\"ci\" time=\"1.500\" />"));
+    assert!(xml.contains("<testcase name=\"test &lt;unit&gt;\" classname=\"ci\" time=\"0.250\">"));
+    assert!(xml.contains("<failure message=\"error code 1\">line 5\nline 6"));
+
+    let json = to_json(&report);
+
+    assert!(json
+        .contains("{\"name\":\"lint\",\"duration_ms\":1500,\"exit_code\":0,\"stderr_tail\":null}"));
+    assert!(json.contains("\"stderr_tail\":\"line 5\\nline 6"));
+}
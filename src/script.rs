@@ -1,14 +1,37 @@
+use std::borrow::Cow;
 use std::env::{join_paths, split_paths};
 use std::ffi::OsString;
 use std::path::PathBuf;
 use std::process::Command;
 
-/// A script has a command and a comment.
+use crate::output::{self, Sink};
+use crate::secrets::Masker;
+use crate::usage::{self, Usage};
+
+/// A script has a command, a comment, and options.
 pub struct Script<'a> {
     /// The command of the script.
-    command: &'a str,
+    command: Cow<'a, str>,
     /// The comment for the script.
-    comment: &'a str,
+    comment: Cow<'a, str>,
+    /// The question to be confirmed before executing the script.
+    confirm: Option<Cow<'a, str>>,
+    /// The lock group of the script, `"true"` means the script has its own lock.
+    exclusive: Option<Cow<'a, str>>,
+    /// Whether the script is a service, which can run in background.
+    service: bool,
+    /// The names of the scripts to be run before the script, separated by commas.
+    depends: Option<Cow<'a, str>>,
+    /// Whether the dependencies of the script are run at the same time.
+    parallel: bool,
+    /// The condition to be waited for before executing the script.
+    wait_for: Option<Cow<'a, str>>,
+    /// The condition which means the service is ready.
+    ready_when: Option<Cow<'a, str>>,
+    /// The time to wait for the conditions, like `30s`.
+    timeout: Option<Cow<'a, str>>,
+    /// Whether the output of the script is written to log files, overriding the settings.
+    log: Option<bool>,
 }
 
 impl<'a> Script<'a> {
@@ -17,59 +40,252 @@ impl<'a> Script<'a> {
     /// ```rs
     /// let script = Script::new("echo hello");
     /// ```
-    pub fn new(command: &'a str) -> Script<'a> {
+    pub fn new(command: impl Into<Cow<'a, str>>) -> Script<'a> {
         Script {
-            command,
-            comment: "This script has no comment.",
+            command: command.into(),
+            comment: Cow::Borrowed("This script has no comment."),
+            confirm: None,
+            exclusive: None,
+            service: false,
+            depends: None,
+            parallel: false,
+            wait_for: None,
+            ready_when: None,
+            timeout: None,
+            log: None,
         }
     }
 
     /// Creates a new `Script`.
+    ///
+    /// As comments are a single line in run.yaml, the lines of the comment are joined with spaces.
     /// # Usage
     /// ```rs
     /// let script_greet = Script::new("echo hey!", "Prints a greeting message.");
     /// ```
-    pub fn new_with_comment(command: &'a str, comment: &'a str) -> Script<'a> {
+    pub fn new_with_comment(
+        command: impl Into<Cow<'a, str>>,
+        comment: impl Into<Cow<'a, str>>,
+    ) -> Script<'a> {
+        let comment = single_line(comment.into());
+
         if comment.is_empty() {
             Script::new(command)
         } else {
-            Script { command, comment }
+            Script {
+                comment,
+                ..Script::new(command)
+            }
         }
     }
 
     /// Returns `self.command`.
-    pub fn command(&self) -> &'a str {
-        self.command
+    pub fn command(&self) -> &str {
+        &self.command
     }
 
     /// Returns `self.comment`.
-    pub fn comment(&self) -> &'a str {
-        self.comment
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// Returns `self.confirm`.
+    pub fn confirm(&self) -> Option<&str> {
+        self.confirm.as_deref()
+    }
+
+    /// Returns `self.exclusive`.
+    pub fn exclusive(&self) -> Option<&str> {
+        self.exclusive.as_deref()
+    }
+
+    /// Returns `self.service`.
+    pub fn service(&self) -> bool {
+        self.service
+    }
+
+    /// Returns the names of the scripts to be run before the script.
+    pub fn depends(&self) -> impl Iterator<Item = &str> {
+        self.depends
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Returns `self.parallel`.
+    pub fn parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Returns `self.wait_for`.
+    pub fn wait_for(&self) -> Option<&str> {
+        self.wait_for.as_deref()
+    }
+
+    /// Returns `self.ready_when`.
+    pub fn ready_when(&self) -> Option<&str> {
+        self.ready_when.as_deref()
+    }
+
+    /// Returns `self.timeout`.
+    pub fn timeout(&self) -> Option<&str> {
+        self.timeout.as_deref()
     }
 
-    /// Executes `self.command`, then returns the exit code.
-    pub fn execute(&self, extra_path: Option<&'static str>) -> i32 {
-        // Get default shell program and c.
-        let (shell, c) = get_shell_and_c();
+    /// Returns `self.log`.
+    pub fn log(&self) -> Option<bool> {
+        self.log
+    }
 
-        // Get $PATH variable with `extra_path`, if `extra_path` is not `None`.
-        let path_var = extra_path.and_then(|extra_path| get_path_var_with(extra_path));
+    /// Sets `self.command`.
+    pub fn set_command(&mut self, command: impl Into<Cow<'a, str>>) {
+        self.command = command.into();
+    }
 
-        // Create a `Command` for launching shell program.
-        let mut command = Command::new(shell);
+    /// Sets `self.confirm`.
+    pub fn set_confirm(&mut self, confirm: impl Into<Cow<'a, str>>) {
+        self.confirm = Some(confirm.into());
+    }
 
-        // If `extra_path` is specified, set "PATH" environment variable to `path_var`.
-        // Else, continue with the default environment variables.
-        match path_var {
-            Some(path_var) => command.arg(c).arg(&self.command).env("PATH", path_var),
-            None => command.arg(c).arg(&self.command),
+    /// Sets `self.exclusive`.
+    pub fn set_exclusive(&mut self, exclusive: impl Into<Cow<'a, str>>) {
+        self.exclusive = Some(exclusive.into());
+    }
+
+    /// Sets `self.service`.
+    pub fn set_service(&mut self, service: bool) {
+        self.service = service;
+    }
+
+    /// Sets `self.depends`.
+    pub fn set_depends(&mut self, depends: impl Into<Cow<'a, str>>) {
+        self.depends = Some(depends.into());
+    }
+
+    /// Sets `self.parallel`.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    /// Sets `self.wait_for`.
+    pub fn set_wait_for(&mut self, wait_for: impl Into<Cow<'a, str>>) {
+        self.wait_for = Some(wait_for.into());
+    }
+
+    /// Sets `self.ready_when`.
+    pub fn set_ready_when(&mut self, ready_when: impl Into<Cow<'a, str>>) {
+        self.ready_when = Some(ready_when.into());
+    }
+
+    /// Sets `self.timeout`.
+    pub fn set_timeout(&mut self, timeout: impl Into<Cow<'a, str>>) {
+        self.timeout = Some(timeout.into());
+    }
+
+    /// Sets `self.log`.
+    pub fn set_log(&mut self, log: bool) {
+        self.log = Some(log);
+    }
+
+    /// Executes `self.command`, then returns the exit code and the resources it used, if they are known.
+    ///
+    /// The arguments are appended to the command, and the environment variables are added to its environment.
+    /// If there are sinks, the output is also passed to them.
+    /// If there is a masker, the secrets are masked in the output.
+    pub fn execute(
+        &self,
+        extra_paths: &[PathBuf],
+        env: &[(String, String)],
+        args: &[String],
+        masker: Option<&Masker>,
+        sinks: &mut [&mut dyn Sink],
+    ) -> (i32, Option<Usage>) {
+        let mut shell = shell(&with_args(&self.command, args), extra_paths);
+
+        shell.envs(env.iter().map(|(name, value)| (name, value)));
+
+        let result = if sinks.is_empty() && masker.is_none() {
+            shell.spawn().and_then(|mut child| usage::wait(&mut child))
+        } else {
+            output::capture(&mut shell, masker, sinks)
+        };
+
+        match result {
+            Ok((status, usage)) => (status.code().unwrap_or(1), usage),
+            Err(_) => (1, None),
         }
-        .status()
-        .and_then(|status| Ok(status.code().unwrap_or(1)))
-        .unwrap_or(1)
     }
 }
 
+/// Joins the non-empty lines of the text with spaces, also splitting it at the other control characters.
+fn single_line(text: Cow<'_, str>) -> Cow<'_, str> {
+    if !text.contains(|ch: char| ch.is_control()) && text.trim().len() == text.len() {
+        return text;
+    }
+
+    Cow::Owned(
+        text.split(|ch: char| ch.is_control())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Appends the arguments to the command, quoting them for the default shell program.
+pub fn with_args(command: &str, args: &[String]) -> String {
+    let mut command = command.to_string();
+
+    for arg in args {
+        command.push(' ');
+        command += &quote(arg);
+    }
+
+    command
+}
+
+/// Quotes the argument, unless it only has characters which are safe for the default shell program.
+pub fn quote(arg: &str) -> String {
+    let is_safe = |ch: char| ch.is_ascii_alphanumeric() || "-_./=:,+@%".contains(ch);
+
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.to_string()
+    } else if cfg!(target_os = "windows") {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Creates a `Command` launching the default shell program to execute `command`.
+pub fn shell(command: &str, extra_paths: &[PathBuf]) -> Command {
+    // Get default shell program and c.
+    let (shell, c) = get_shell_and_c();
+
+    // Get $PATH variable with `extra_paths`, if there are any.
+    let path_var = get_path_var_with(extra_paths);
+
+    // Create a `Command` for launching shell program.
+    let mut shell = Command::new(shell);
+
+    shell.arg(c).arg(command);
+
+    // The references to the other scripts run this executable, as it may not be on `PATH`.
+    if let Ok(exe) = std::env::current_exe() {
+        shell.env("RUN", exe);
+    }
+
+    // If `extra_paths` are specified, set "PATH" environment variable to `path_var`.
+    // Else, continue with the default environment variables.
+    if let Some(path_var) = path_var {
+        shell.env("PATH", path_var);
+    }
+
+    shell
+}
+
 /// Returns shell and c based on client OS.
 fn get_shell_and_c() -> (&'static str, &'static str) {
     if cfg!(target_os = "windows") {
@@ -79,9 +295,13 @@ fn get_shell_and_c() -> (&'static str, &'static str) {
     }
 }
 
-/// Adds `extra_path` to `$PATH` environment variable, then returns it.
-fn get_path_var_with(extra_path: &'static str) -> Option<OsString> {
-    let mut paths: Vec<PathBuf> = split_paths(&std::env::var_os("PATH")?).collect();
-    paths.push(PathBuf::from(extra_path));
+/// Adds `extra_paths` to the beginning of `$PATH` environment variable in order, then returns it.
+fn get_path_var_with(extra_paths: &[PathBuf]) -> Option<OsString> {
+    if extra_paths.is_empty() {
+        return None;
+    }
+
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let paths = extra_paths.iter().cloned().chain(split_paths(&path_var));
     join_paths(paths).ok()
 }
@@ -0,0 +1,116 @@
+use std::{env, fs};
+
+/// The file which the values of the secrets are read from, if they aren't in the environment.
+pub const DOTENV: &str = ".env";
+
+/// The text which the secrets are replaced with.
+const MASK: &[u8] = b"***";
+
+/// The minimum length of a value to be masked, so short values don't mask most of the output.
+const MIN_LEN: usize = 4;
+
+/// Replaces the values of the secrets with `***` in the output.
+pub struct Masker {
+    /// The values of the secrets, the longest first.
+    secrets: Vec<Vec<u8>>,
+}
+
+impl Masker {
+    /// Creates a masker for the environment variables with the given names.
+    ///
+    /// The values are read from the environment, or from the `.env` file if they aren't set.
+    /// Returns `None` if none of them has a value.
+    pub fn new<'a>(names: impl Iterator<Item = &'a str>) -> Option<Masker> {
+        let dotenv = fs::read_to_string(DOTENV).unwrap_or_default();
+
+        let mut secrets: Vec<Vec<u8>> = names
+            .filter_map(|name| env::var(name).ok().or_else(|| dotenv_value(&dotenv, name)))
+            .filter(|value| value.len() >= MIN_LEN)
+            .map(String::into_bytes)
+            .collect();
+
+        if secrets.is_empty() {
+            return None;
+        }
+
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+
+        Some(Masker { secrets })
+    }
+
+    /// Masks the secrets in `pending`, then returns the masked output and removes it from `pending`.
+    ///
+    /// The end of `pending` is kept if it may be the beginning of a secret, unless it's the end of the output.
+    pub fn mask(&self, pending: &mut Vec<u8>, end: bool) -> Vec<u8> {
+        let mut masked = Vec::with_capacity(pending.len());
+        let mut i = 0;
+
+        while i < pending.len() {
+            let rest = &pending[i..];
+
+            if let Some(secret) = self.secrets.iter().find(|secret| rest.starts_with(secret)) {
+                masked.extend_from_slice(MASK);
+                i += secret.len();
+            } else if !end && self.secrets.iter().any(|secret| secret.starts_with(rest)) {
+                break;
+            } else {
+                masked.push(pending[i]);
+                i += 1;
+            }
+        }
+
+        pending.drain(..i);
+
+        masked
+    }
+}
+
+/// Returns the value of the key in the content of a `.env` file, if any.
+fn dotenv_value(dotenv: &str, key: &str) -> Option<String> {
+    dotenv.lines().find_map(|line| {
+        let line = line.trim();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (name, value) = line.split_once('=')?;
+
+        if name.trim() != key {
+            return None;
+        }
+
+        let value = value.trim();
+
+        // Remove the quotes around the value, if any.
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+            .unwrap_or(value);
+
+        Some(value.to_string())
+    })
+}
+
+#[test]
+fn test() {
+    let masker = Masker {
+        secrets: vec![b"hunter22".to_vec(), b"s3cr3t".to_vec()],
+    };
+
+    // The secrets are masked, even if they are split into chunks.
+    let mut pending = b"token=s3c".to_vec();
+    assert_eq!(masker.mask(&mut pending, false), b"token=");
+    assert_eq!(pending, b"s3c");
+
+    pending.extend_from_slice(b"r3t and hunter2");
+    assert_eq!(masker.mask(&mut pending, false), b"*** and ");
+    assert_eq!(pending, b"hunter2");
+
+    // The end of the output is written, if it isn't a secret.
+    assert_eq!(masker.mask(&mut pending, true), b"hunter2");
+    assert!(pending.is_empty());
+
+    // The values are read from `.env` files.
+    let dotenv = "# comment\nexport TOKEN=\"abc def\"\nKEY = value\n";
+    assert_eq!(dotenv_value(dotenv, "TOKEN").as_deref(), Some("abc def"));
+    assert_eq!(dotenv_value(dotenv, "KEY").as_deref(), Some("value"));
+    assert_eq!(dotenv_value(dotenv, "OTHER"), None);
+}
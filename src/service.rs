@@ -0,0 +1,499 @@
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex, Once,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{beautify::Beautify, script, state};
+
+/// The services started by this process.
+///
+/// They are kept to be reaped, as they don't disappear until then, even if they exit.
+static CHILDREN: Mutex<Vec<Child>> = Mutex::new(Vec::new());
+
+/// The process IDs of the services to be stopped, if this process is interrupted, `0` being an empty slot.
+///
+/// They are atomics instead of a `Mutex`, cuz they are read in a signal handler.
+static STOP_ON_INTERRUPT: [AtomicU32; 64] = [const { AtomicU32::new(0) }; 64];
+
+/// The status of a service.
+pub enum Status {
+    /// The service is running with the process ID, since the time as seconds since Unix epoch.
+    Running(u32, u64),
+    /// The service is not running, with its last exit code if it's known.
+    Stopped(Option<i32>),
+}
+
+/// The error type for service operations.
+pub enum ServiceError {
+    /// The service is not able to be started.
+    CannotBeStarted(String),
+    /// The service is not able to be stopped.
+    CannotBeStopped(String),
+    /// The logs of the service are not able to be read.
+    NoLogs(String),
+}
+
+// Implement `Display` trait for `ServiceError`.
+impl Display for ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CannotBeStarted(name) => write!(f, "{} {}", "can't start".red(), name.green()),
+            Self::CannotBeStopped(name) => write!(f, "{} {}", "can't stop".red(), name.green()),
+            Self::NoLogs(name) => write!(
+                f,
+                "{} {}\n\n{}",
+                name.green(),
+                "has no logs".red(),
+                "type `run up` to start it".yellow()
+            ),
+        }
+    }
+}
+
+/// Returns the path of the file of the service with the given extension inside `.run/services`, without creating anything.
+fn path(name: &str, extension: &str) -> PathBuf {
+    state::path("services").join(format!("{}.{}", state::file_name(name), extension))
+}
+
+/// Returns the path of the log file of the service.
+pub fn log_path(name: &str) -> PathBuf {
+    path(name, "log")
+}
+
+/// Returns the current status of the service.
+pub fn status(name: &str) -> Status {
+    let read = |extension| fs::read_to_string(path(name, extension)).ok();
+
+    status_of(read("exit").as_deref(), read("pid").as_deref())
+}
+
+/// Returns the status of a service from the contents of its exit file and its pid file.
+fn status_of(exit: Option<&str>, pid: Option<&str>) -> Status {
+    // The exit code is recorded when the service exits, so it can't be running if there is one.
+    let exit_code = exit.and_then(|content| content.trim().parse().ok());
+
+    if exit_code.is_some() {
+        return Status::Stopped(exit_code);
+    }
+
+    // Try to read the process ID, the start time, and the identity of the service.
+    let running = pid
+        .and_then(|content| {
+            let mut lines = content.lines();
+            let pid: u32 = lines.next()?.parse().ok()?;
+            let since: u64 = lines.next()?.parse().ok()?;
+            let identity = lines.next()?;
+            Some((pid, since, identity))
+        })
+        .filter(|(pid, _, identity)| is_alive(*pid, identity));
+
+    match running {
+        Some((pid, since, _)) => Status::Running(pid, since),
+        None => Status::Stopped(None),
+    }
+}
+
+/// Starts the service in background, then returns its process ID.
+///
+/// The output of the service is written to its log file.
+pub fn start(name: &str, command: &str, extra_paths: &[PathBuf]) -> Result<u32, ServiceError> {
+    let cannot_be_started = |_| ServiceError::CannotBeStarted(name.to_string());
+
+    // `.run/services` is only created when a service is started.
+    state::dir("services").map_err(cannot_be_started)?;
+
+    let log_path = log_path(name);
+    let pid_path = path(name, "pid");
+    let exit_path = path(name, "exit");
+
+    // The exit code of the last run isn't valid anymore.
+    let _ = fs::remove_file(&exit_path);
+
+    let log = File::create(log_path).map_err(cannot_be_started)?;
+    let log_clone = log.try_clone().map_err(cannot_be_started)?;
+
+    // Make the shell record the exit code of the service, when the service exits by itself.
+    let command = if cfg!(unix) {
+        format!("(\n{}\n)\necho $? > {}", command, exit_path.display())
+    } else {
+        command.to_string()
+    };
+
+    let mut shell = script::shell(&command, extra_paths);
+
+    shell.stdin(Stdio::null()).stdout(log).stderr(log_clone);
+
+    detach(&mut shell);
+
+    let child = shell.spawn().map_err(cannot_be_started)?;
+    let pid = child.id();
+
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.push(child);
+    }
+
+    // The identity tells the service apart from a process which gets the same ID after it exits.
+    let identity = identity(pid).unwrap_or_default();
+
+    fs::write(
+        pid_path,
+        format!("{}\n{}\n{}\n", pid, state::now(), identity),
+    )
+    .map_err(cannot_be_started)?;
+
+    Ok(pid)
+}
+
+/// Stops the service with all the processes started by it.
+///
+/// Returns `false` if the service isn't running.
+pub fn stop(name: &str) -> Result<bool, ServiceError> {
+    let pid = match status(name) {
+        Status::Running(pid, _) => pid,
+        Status::Stopped(_) => return Ok(false),
+    };
+
+    // It's known to be the service, as the status checks its identity.
+    let identity = identity(pid).unwrap_or_default();
+    let is_alive = |pid| is_alive(pid, &identity);
+
+    terminate(pid, false);
+
+    // Give the service some time to exit gracefully, then kill it.
+    let start_time = Instant::now();
+
+    while is_alive(pid) {
+        if start_time.elapsed() > Duration::from_secs(5) {
+            terminate(pid, true);
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    if is_alive(pid) {
+        return Err(ServiceError::CannotBeStopped(name.to_string()));
+    }
+
+    // We don't need to be warned, cuz a stale file is ignored, as the process isn't alive.
+    #[allow(unused_must_use)]
+    {
+        fs::remove_file(path(name, "pid"));
+    }
+
+    forget_on_interrupt(pid);
+
+    Ok(true)
+}
+
+/// Makes the service with the given process ID be stopped, if this process is interrupted, like by Ctrl-C.
+///
+/// The services run in their own process groups, so they don't receive the interruption themselves.
+pub fn stop_on_interrupt(pid: u32) {
+    static HANDLER: Once = Once::new();
+
+    HANDLER.call_once(install_interrupt_handler);
+
+    // If all the slots are taken, the service is only stopped when the script is finished.
+    let _ = STOP_ON_INTERRUPT.iter().find(|slot| {
+        slot.compare_exchange(0, pid, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    });
+}
+
+/// Makes the service with the given process ID not be stopped anymore, if this process is interrupted.
+fn forget_on_interrupt(pid: u32) {
+    for slot in &STOP_ON_INTERRUPT {
+        let _ = slot.compare_exchange(pid, 0, Ordering::SeqCst, Ordering::SeqCst);
+    }
+}
+
+/// Stops the services to be stopped on an interruption.
+fn stop_interrupted() {
+    for slot in &STOP_ON_INTERRUPT {
+        let pid = slot.swap(0, Ordering::SeqCst);
+
+        if pid != 0 {
+            terminate(pid, false);
+        }
+    }
+}
+
+/// Prints the last lines of the log file of the service.
+///
+/// If `follow` is `true`, keeps printing the new lines until the process is terminated.
+pub fn print_logs(name: &str, follow: bool) -> Result<(), ServiceError> {
+    let no_logs = |_| ServiceError::NoLogs(name.to_string());
+
+    let mut file = File::open(log_path(name)).map_err(no_logs)?;
+
+    let lines: Vec<String> = BufReader::new(&mut file)
+        .lines()
+        .map_while(Result::ok)
+        .collect();
+
+    for line in lines.iter().skip(lines.len().saturating_sub(50)) {
+        println!("{}", line);
+    }
+
+    if !follow {
+        return Ok(());
+    }
+
+    // Continue from the end of the file.
+    let mut position = file.seek(SeekFrom::End(0)).map_err(no_logs)?;
+    let mut buf = String::new();
+
+    loop {
+        thread::sleep(Duration::from_millis(200));
+
+        // If the service is restarted, the log file is truncated, so start over.
+        let len = file.metadata().map_err(no_logs)?.len();
+
+        if len < position {
+            file = File::open(log_path(name)).map_err(no_logs)?;
+            position = 0;
+        }
+
+        file.seek(SeekFrom::Start(position)).map_err(no_logs)?;
+
+        buf.clear();
+        position += file.read_to_string(&mut buf).map_err(no_logs)? as u64;
+
+        print!("{}", buf);
+    }
+}
+
+/// Reaps the services started by this process, which are exited.
+fn reap() {
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.retain_mut(|child| match child.try_wait() {
+            Ok(Some(_)) => {
+                forget_on_interrupt(child.id());
+                false
+            }
+            _ => true,
+        });
+    }
+}
+
+/// Formats the given seconds like `1h 2m 3s`.
+pub fn format_secs(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m {}s", secs / 3600, secs % 3600 / 60, secs % 60),
+    }
+}
+
+/// Makes the process started by `command` the leader of a new process group.
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+}
+
+/// Makes the process started by `command` the leader of a new process group.
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+
+    // `CREATE_NEW_PROCESS_GROUP` and `DETACHED_PROCESS`.
+    command.creation_flags(0x0000_0200 | 0x0000_0008);
+}
+
+/// Returns `true` if the process with the given ID is alive and still has the given identity.
+fn is_alive(pid: u32, identity: &str) -> bool {
+    reap();
+
+    self::identity(pid).is_some_and(|current| current == identity)
+}
+
+/// Returns the identity of the process with the given ID, which is its start time in clock ticks since boot.
+///
+/// Returns `None` if there is no such process, or if it's a zombie.
+#[cfg(target_os = "linux")]
+fn identity(pid: u32) -> Option<String> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    // The name of the process is in parentheses and can contain spaces, so the fields are after it.
+    let (_, fields) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+
+    // The state is the 3rd field and the start time is the 22nd one.
+    match fields.first() {
+        Some(&"Z") | Some(&"X") | None => None,
+        Some(_) => fields.get(19).map(|start_time| start_time.to_string()),
+    }
+}
+
+/// Returns the identity of the process with the given ID, which is its start time.
+///
+/// Returns `None` if there is no such process, or if it's a zombie.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn identity(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "stat=", "-o", "lstart=", "-p", &pid.to_string()])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    let (state, start_time) = output.trim().split_once(char::is_whitespace)?;
+
+    match state.starts_with('Z') {
+        true => None,
+        false => Some(start_time.trim().to_string()),
+    }
+}
+
+/// Returns the identity of the process with the given ID, which is the name of its image.
+///
+/// Returns `None` if there is no such process.
+#[cfg(windows)]
+fn identity(pid: u32) -> Option<String> {
+    let output = Command::new("tasklist")
+        .args(["/NH", "/FO", "CSV", "/FI", &format!("PID eq {}", pid)])
+        .output()
+        .ok()?;
+
+    // The line is like `"cmd.exe","1234",...`, or a message if there is no such process.
+    let output = String::from_utf8_lossy(&output.stdout);
+    let image = output.trim().strip_prefix('"')?.split('"').next()?;
+
+    Some(image.to_string())
+}
+
+/// Sends a termination signal to the process group with the given ID.
+///
+/// If `force` is `true`, the processes are killed.
+#[cfg(unix)]
+fn terminate(pid: u32, force: bool) {
+    // `SIGKILL` and `SIGTERM`, which have the same numbers on every Unix.
+    let signal = if force { 9 } else { 15 };
+
+    // SAFETY: `kill` only sends a signal, a negative ID means the process group.
+    // We don't need to check the result, cuz the process is checked afterwards.
+    unsafe {
+        kill(-(pid as i32), signal);
+    }
+}
+
+#[cfg(unix)]
+extern "C" {
+    fn kill(pid: i32, signal: i32) -> i32;
+    fn signal(signal: i32, handler: usize) -> usize;
+    fn raise(signal: i32) -> i32;
+}
+
+/// Installs the handler stopping the services on `SIGINT`, `SIGTERM`, and `SIGHUP`.
+#[cfg(unix)]
+fn install_interrupt_handler() {
+    extern "C" fn handle(number: i32) {
+        stop_interrupted();
+
+        // Exit as if the signal isn't handled, by restoring the default handler, which is `SIG_DFL`.
+        // SAFETY: `signal` and `raise` are safe to be called in a signal handler.
+        unsafe {
+            signal(number, 0);
+            raise(number);
+        }
+    }
+
+    // `SIGINT`, `SIGTERM`, and `SIGHUP`, which have the same numbers on every Unix.
+    for number in [2, 15, 1] {
+        // SAFETY: The handler only uses atomics and async-signal-safe functions.
+        unsafe {
+            signal(number, handle as extern "C" fn(i32) as usize);
+        }
+    }
+}
+
+/// Installs the handler stopping the services on Ctrl-C, Ctrl-Break, and closing the console.
+#[cfg(windows)]
+fn install_interrupt_handler() {
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: usize, add: i32) -> i32;
+    }
+
+    extern "system" fn handle(_: u32) -> i32 {
+        stop_interrupted();
+
+        // Let the default handler exit the process.
+        0
+    }
+
+    // SAFETY: The handler is a valid function for the whole life of the process.
+    unsafe {
+        SetConsoleCtrlHandler(handle as extern "system" fn(u32) -> i32 as usize, 1);
+    }
+}
+
+/// Terminates the process tree with the given ID.
+///
+/// If `force` is `true`, the processes are killed.
+#[cfg(windows)]
+fn terminate(pid: u32, force: bool) {
+    let mut taskkill = Command::new("taskkill");
+
+    taskkill.args(["/T", "/PID", &pid.to_string()]);
+
+    if force {
+        taskkill.arg("/F");
+    }
+
+    // We don't need to be warned, cuz the process is checked afterwards.
+    #[allow(unused_must_use)]
+    {
+        taskkill
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+#[test]
+fn test() {
+    let pid = std::process::id();
+    let own_identity = identity(pid).unwrap();
+
+    // A recorded exit code means the service is stopped.
+    let pid_file = format!("{}\n100\n{}\n", pid, own_identity);
+
+    assert!(matches!(
+        status_of(Some("3\n"), Some(&pid_file)),
+        Status::Stopped(Some(3))
+    ));
+
+    // A live process with the recorded identity is the service.
+    assert!(matches!(
+        status_of(None, Some(&pid_file)),
+        Status::Running(running_pid, 100) if running_pid == pid
+    ));
+
+    // A process which reuses the ID isn't the service.
+    let reused = format!("{}\n100\nsomething else\n", pid);
+
+    assert!(matches!(
+        status_of(None, Some(&reused)),
+        Status::Stopped(None)
+    ));
+
+    // The pid files without an identity aren't trusted.
+    let old = format!("{}\n100\n", pid);
+
+    assert!(matches!(status_of(None, Some(&old)), Status::Stopped(None)));
+
+    // Without the files, the service is stopped.
+    assert!(matches!(status_of(None, None), Status::Stopped(None)));
+}
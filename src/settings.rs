@@ -0,0 +1,112 @@
+/// The settings of run, which are written in the `.settings` block of run.yaml.
+#[derive(Default)]
+pub struct Settings {
+    /// Whether the output of every script is written to log files.
+    log: bool,
+    /// Whether each line of the log files begins with the time it's written.
+    log_timestamps: bool,
+    /// The number of log files kept for each script.
+    log_keep: Option<usize>,
+    /// Whether the resources used by the scripts are displayed after they exit.
+    stats: bool,
+    /// The time a script must take to notify when it's finished, or `false` to never notify.
+    notify_after: Option<String>,
+    /// The names of the environment variables whose values are masked in the output, separated by commas.
+    secrets: Option<String>,
+    /// Whether the scripts are run like npm runs them, with their `pre` and `post` scripts and its environment variables.
+    npm_compat: bool,
+    /// The directories of the executables used by the scripts, separated by commas.
+    path: Option<String>,
+}
+
+impl Settings {
+    /// Returns `self.log`.
+    pub fn log(&self) -> bool {
+        self.log
+    }
+
+    /// Returns `self.log_timestamps`.
+    pub fn log_timestamps(&self) -> bool {
+        self.log_timestamps
+    }
+
+    /// Returns `self.log_keep`.
+    pub fn log_keep(&self) -> Option<usize> {
+        self.log_keep
+    }
+
+    /// Returns `self.stats`.
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+
+    /// Returns `self.notify_after`.
+    pub fn notify_after(&self) -> Option<&str> {
+        self.notify_after.as_deref()
+    }
+
+    /// Returns an iterator over the names in `self.secrets`.
+    pub fn secrets(&self) -> impl Iterator<Item = &str> {
+        self.secrets
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Returns `self.npm_compat`.
+    pub fn npm_compat(&self) -> bool {
+        self.npm_compat
+    }
+
+    /// Returns an iterator over the directories in `self.path`.
+    pub fn path(&self) -> impl Iterator<Item = &str> {
+        self.path
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+    }
+
+    /// Sets `self.log`.
+    pub fn set_log(&mut self, log: bool) {
+        self.log = log;
+    }
+
+    /// Sets `self.log_timestamps`.
+    pub fn set_log_timestamps(&mut self, log_timestamps: bool) {
+        self.log_timestamps = log_timestamps;
+    }
+
+    /// Sets `self.log_keep`.
+    pub fn set_log_keep(&mut self, log_keep: usize) {
+        self.log_keep = Some(log_keep);
+    }
+
+    /// Sets `self.stats`.
+    pub fn set_stats(&mut self, stats: bool) {
+        self.stats = stats;
+    }
+
+    /// Sets `self.notify_after`.
+    pub fn set_notify_after(&mut self, notify_after: &str) {
+        self.notify_after = Some(notify_after.to_string());
+    }
+
+    /// Sets `self.secrets`.
+    pub fn set_secrets(&mut self, secrets: &str) {
+        self.secrets = Some(secrets.to_string());
+    }
+
+    /// Sets `self.npm_compat`.
+    pub fn set_npm_compat(&mut self, npm_compat: bool) {
+        self.npm_compat = npm_compat;
+    }
+
+    /// Sets `self.path`.
+    pub fn set_path(&mut self, path: &str) {
+        self.path = Some(path.to_string());
+    }
+}
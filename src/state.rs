@@ -0,0 +1,77 @@
+use std::{
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The directory holding the state of run, like locks, in the current directory.
+pub const STATE_DIR: &str = ".run";
+
+/// Returns the path of the given file or directory inside `.run`, without creating anything.
+///
+/// It's used to read the state, which may not exist yet.
+pub fn path(name: &str) -> PathBuf {
+    Path::new(STATE_DIR).join(name)
+}
+
+/// Returns the path of the given directory inside `.run`.
+///
+/// Creates the directory, if it doesn't exist. It's used to write the state.
+pub fn dir(name: &str) -> Result<PathBuf> {
+    let path = self::path(name);
+
+    fs::create_dir_all(&path)?;
+
+    // The state is local, so keep it out of version control.
+    let gitignore = Path::new(STATE_DIR).join(".gitignore");
+
+    if !gitignore.exists() {
+        fs::write(gitignore, "*\n")?;
+    }
+
+    Ok(path)
+}
+
+/// Converts the given name to a name that can be used as a file name on every OS.
+///
+/// Different names are never converted to the same file name, even on case-insensitive file systems.
+/// `_` is written as `__`, and every byte of the other characters, including the uppercase letters, as `_` and its hex code.
+pub fn file_name(name: &str) -> String {
+    let mut file_name = String::with_capacity(name.len());
+
+    for ch in name.chars() {
+        match ch {
+            'a'..='z' | '0'..='9' | '-' | '.' => file_name.push(ch),
+            '_' => file_name.push_str("__"),
+            _ => {
+                let mut bytes = [0; 4];
+
+                for byte in ch.encode_utf8(&mut bytes).bytes() {
+                    file_name.push_str(&format!("_{:02x}", byte));
+                }
+            }
+        }
+    }
+
+    file_name
+}
+
+/// Returns the current time as seconds since Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[test]
+fn test() {
+    assert_eq!(file_name("build"), "build");
+    assert_eq!(file_name("npm:build"), "npm_3abuild");
+
+    // Different names must never share a file name.
+    assert_ne!(file_name("a/b"), file_name("a_b"));
+    assert_ne!(file_name("a_b"), file_name("a_5fb"));
+    assert_ne!(file_name("Build"), file_name("build"));
+}
@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+
+use crate::node;
+
+/// The directory of the executables in the virtual environment of Python.
+const VENV_BIN: &str = if cfg!(target_os = "windows") {
+    ".venv/Scripts"
+} else {
+    ".venv/bin"
+};
+
+/// The directory of the executables installed by Composer.
+const COMPOSER_BIN: &str = "vendor/bin";
+
+/// The directory of the binstubs of Bundler.
+const RUBY_BIN: &str = "bin";
+
+/// The directory of the executables built by Cargo.
+const RUST_BIN: &str = "target/debug";
+
+/// A provider of the directories of project-local executables, which are added to the beginning of `PATH`.
+pub struct Provider {
+    /// The name of the provider, like `python`.
+    pub name: &'static str,
+    /// Why the provider is activated or not.
+    pub reason: String,
+    /// The directories which are added to `PATH` if they exist, in the order they are searched.
+    pub candidates: Vec<PathBuf>,
+}
+
+impl Provider {
+    /// Creates a provider of the directory, which is activated if the directory exists.
+    fn of_dir(name: &'static str, dir: &str, reason: &str) -> Provider {
+        let reason = match Path::new(dir).is_dir() {
+            true => format!("{} exists", reason),
+            false => format!("{} doesn't exist", dir),
+        };
+
+        Provider {
+            name,
+            reason,
+            candidates: vec![absolute(Path::new(dir))],
+        }
+    }
+
+    /// Creates a provider which doesn't apply to the project, so it has no directory.
+    fn inactive(name: &'static str, reason: String) -> Provider {
+        Provider {
+            name,
+            reason,
+            candidates: vec![],
+        }
+    }
+
+    /// Returns the directories which are added to `PATH` now, as they exist.
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.candidates.iter().filter(|path| path.is_dir())
+    }
+
+    /// Returns `true` if the provider adds any directory to `PATH`.
+    pub fn is_active(&self) -> bool {
+        self.paths().next().is_some()
+    }
+}
+
+/// Returns the providers in the order their directories are searched, with the directories given by the user first.
+pub fn providers<'u>(user_paths: impl Iterator<Item = &'u str>) -> Vec<Provider> {
+    vec![
+        user(user_paths),
+        node(),
+        python(),
+        composer(),
+        ruby(),
+        rust(),
+    ]
+}
+
+/// Returns the directories the providers may add to `PATH`, in the order they are searched, whether they exist or not.
+///
+/// They are found once per run, and only the existing ones are used, as a dependency may create them, like `npm install`.
+pub fn candidate_paths<'u>(user_paths: impl Iterator<Item = &'u str>) -> Vec<PathBuf> {
+    providers(user_paths)
+        .into_iter()
+        .flat_map(|provider| provider.candidates)
+        .collect()
+}
+
+/// Returns the absolute path, so the scripts changing their directory can still find the executables.
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Returns the provider of the directories given by `path` in the settings.
+fn user<'u>(user_paths: impl Iterator<Item = &'u str>) -> Provider {
+    let candidates: Vec<PathBuf> = user_paths.map(|path| absolute(Path::new(path))).collect();
+
+    let missing: Vec<String> = candidates
+        .iter()
+        .filter(|path| !path.is_dir())
+        .map(|path| format!("{} doesn't exist", path.display()))
+        .collect();
+
+    let reason = match (missing.len() == candidates.len(), missing.is_empty()) {
+        (true, true) => "no path is set in run.yaml".to_string(),
+        (_, true) => "set in run.yaml".to_string(),
+        (true, false) => missing.join(", "),
+        (false, false) => format!("set in run.yaml, but {}", missing.join(", ")),
+    };
+
+    Provider {
+        name: "run.yaml",
+        reason,
+        candidates,
+    }
+}
+
+/// Returns the provider of the executables installed by the Node.js package managers.
+fn node() -> Provider {
+    let candidates = node::bin_candidates();
+
+    let reason = if !candidates.iter().any(|bin| bin.is_dir()) {
+        "node_modules/.bin doesn't exist in the current directory or its parents".to_string()
+    } else {
+        match node::package_manager() {
+            Some((package_manager, _)) => format!(
+                "node_modules/.bin exists, installed by {}",
+                package_manager.name()
+            ),
+            None => "node_modules/.bin exists".to_string(),
+        }
+    };
+
+    Provider {
+        name: "node",
+        reason,
+        candidates,
+    }
+}
+
+/// Returns the provider of the executables in the virtual environment of Python.
+fn python() -> Provider {
+    Provider::of_dir("python", VENV_BIN, "the virtual environment .venv")
+}
+
+/// Returns the provider of the executables installed by Composer.
+fn composer() -> Provider {
+    Provider::of_dir("composer", COMPOSER_BIN, "vendor/bin of Composer")
+}
+
+/// Returns the provider of the binstubs of Bundler, which are only used along with a Gemfile.
+fn ruby() -> Provider {
+    match Path::new("Gemfile").is_file() {
+        true => Provider::of_dir("ruby", RUBY_BIN, "Gemfile and the binstubs in bin"),
+        false => Provider::inactive("ruby", "Gemfile doesn't exist".to_string()),
+    }
+}
+
+/// Returns the provider of the executables built by Cargo, which are only used along with a Cargo.toml.
+fn rust() -> Provider {
+    match Path::new("Cargo.toml").is_file() {
+        true => Provider::of_dir("rust", RUST_BIN, "Cargo.toml and target/debug"),
+        false => Provider::inactive("rust", "Cargo.toml doesn't exist".to_string()),
+    }
+}
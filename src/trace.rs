@@ -0,0 +1,108 @@
+use std::{
+    fmt::Display,
+    fs,
+    sync::Mutex,
+    thread::{self, ThreadId},
+    time::Instant,
+};
+
+use crate::{beautify::Beautify, json};
+
+/// The trace being recorded, if it's asked for.
+static TRACE: Mutex<Option<Trace>> = Mutex::new(None);
+
+/// A timeline of a run, saved as a trace event file of Chrome, which can be opened in Perfetto too.
+struct Trace {
+    /// The file which the trace is saved to.
+    path: String,
+    /// The time the trace is started, which the timestamps are relative to.
+    start: Instant,
+    /// The threads which recorded spans, their indexes are used as thread ids.
+    threads: Vec<ThreadId>,
+    /// The events, which are already JSON objects.
+    events: Vec<String>,
+}
+
+/// The error type for the trace.
+pub struct TraceError(String);
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            "the trace can't be saved to".red(),
+            self.0.yellow()
+        )
+    }
+}
+
+/// Starts recording a trace, to be saved to the file at the end.
+pub fn init(path: &str) {
+    if let Ok(mut trace) = TRACE.lock() {
+        *trace = Some(Trace {
+            path: path.to_string(),
+            start: Instant::now(),
+            threads: vec![],
+            events: vec![],
+        });
+    }
+}
+
+/// Records a span from `start` to now, if a trace is being recorded.
+///
+/// The values of the arguments must already be JSON, like the ones created by `json::string`.
+pub fn span(name: &str, category: &str, start: Instant, args: &[(&str, String)]) {
+    let Ok(mut trace) = TRACE.lock() else {
+        return;
+    };
+
+    let Some(trace) = trace.as_mut() else {
+        return;
+    };
+
+    // The spans of each thread are shown in a separate track.
+    let thread_id = thread::current().id();
+
+    let tid = match trace.threads.iter().position(|id| *id == thread_id) {
+        Some(index) => index,
+        None => {
+            trace.threads.push(thread_id);
+            trace.threads.len() - 1
+        }
+    };
+
+    let args: Vec<String> = args
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json::string(key), value))
+        .collect();
+
+    trace.events.push(format!(
+        "{{\"name\":{},\"cat\":{},\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":1,\"tid\":{},\"args\":{{{}}}}}",
+        json::string(name),
+        json::string(category),
+        start.saturating_duration_since(trace.start).as_micros(),
+        start.elapsed().as_micros(),
+        tid,
+        args.join(",")
+    ));
+}
+
+/// Saves the trace to its file, if a trace is being recorded.
+pub fn save() -> Result<(), TraceError> {
+    let Ok(mut trace) = TRACE.lock() else {
+        return Ok(());
+    };
+
+    // The trace is saved once, even if it's asked again.
+    let Some(trace) = trace.take() else {
+        return Ok(());
+    };
+
+    let content = format!(
+        "{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ms\"}}\n",
+        trace.events.join(",\n")
+    );
+
+    fs::write(&trace.path, content).map_err(|_| TraceError(trace.path))
+}
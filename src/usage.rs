@@ -0,0 +1,164 @@
+use std::{
+    fmt::Display,
+    io::Result,
+    process::{Child, ExitStatus},
+    time::Duration,
+};
+
+/// The resources used by a process, until it exits.
+pub struct Usage {
+    /// The CPU time spent in user mode.
+    user: Duration,
+    /// The CPU time spent in kernel mode.
+    system: Duration,
+    /// The maximum resident set size, in bytes.
+    max_rss: u64,
+    /// The page faults that needed I/O.
+    major_faults: u64,
+    /// The page faults that didn't need I/O.
+    minor_faults: u64,
+    /// The context switches caused by waiting for a resource.
+    voluntary_switches: u64,
+    /// The context switches caused by the scheduler.
+    involuntary_switches: u64,
+}
+
+// Implement `Display` trait for `Usage`, like `cpu 0.85s user, 0.12s sys  memory 54.20 MB max  ...`.
+impl Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cpu {:.2?} user, {:.2?} sys  memory {} max  faults {} major, {} minor  switches {} voluntary, {} involuntary",
+            self.user,
+            self.system,
+            format_bytes(self.max_rss),
+            self.major_faults,
+            self.minor_faults,
+            self.voluntary_switches,
+            self.involuntary_switches
+        )
+    }
+}
+
+/// Formats the bytes, like `54.20 MB`.
+fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.2} {}", value, units[unit]),
+    }
+}
+
+/// Waits for the child to exit, then returns its exit status and the resources it used.
+///
+/// The resources are only known on Unix, where the child is waited with `wait4`.
+#[cfg(unix)]
+pub fn wait(child: &mut Child) -> Result<(ExitStatus, Option<Usage>)> {
+    use std::{
+        ffi::{c_int, c_long},
+        io::{Error, ErrorKind},
+        os::unix::process::ExitStatusExt,
+    };
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Timeval {
+        sec: c_long,
+        #[cfg(target_os = "macos")]
+        usec: i32,
+        #[cfg(not(target_os = "macos"))]
+        usec: c_long,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Rusage {
+        utime: Timeval,
+        stime: Timeval,
+        maxrss: c_long,
+        ixrss: c_long,
+        idrss: c_long,
+        isrss: c_long,
+        minflt: c_long,
+        majflt: c_long,
+        nswap: c_long,
+        inblock: c_long,
+        oublock: c_long,
+        msgsnd: c_long,
+        msgrcv: c_long,
+        nsignals: c_long,
+        nvcsw: c_long,
+        nivcsw: c_long,
+    }
+
+    extern "C" {
+        fn wait4(pid: i32, status: *mut c_int, options: c_int, rusage: *mut Rusage) -> i32;
+    }
+
+    let mut status = 0;
+    let mut rusage = Rusage::default();
+
+    loop {
+        // SAFETY: The pointers are valid for the call, and the child isn't waited anywhere else.
+        let pid = unsafe { wait4(child.id() as i32, &mut status, 0, &mut rusage) };
+
+        if pid != -1 {
+            break;
+        }
+
+        let err = Error::last_os_error();
+
+        if err.kind() != ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+
+    let duration = |timeval: &Timeval| {
+        Duration::from_secs(timeval.sec.max(0) as u64)
+            + Duration::from_micros(timeval.usec.max(0) as u64)
+    };
+
+    let count = |value: c_long| value.max(0) as u64;
+
+    // The maximum resident set size is in bytes on macOS, and in kilobytes elsewhere.
+    let max_rss = match cfg!(target_os = "macos") {
+        true => count(rusage.maxrss),
+        false => count(rusage.maxrss) * 1024,
+    };
+
+    let usage = Usage {
+        user: duration(&rusage.utime),
+        system: duration(&rusage.stime),
+        max_rss,
+        major_faults: count(rusage.majflt),
+        minor_faults: count(rusage.minflt),
+        voluntary_switches: count(rusage.nvcsw),
+        involuntary_switches: count(rusage.nivcsw),
+    };
+
+    Ok((ExitStatus::from_raw(status), Some(usage)))
+}
+
+/// Waits for the child to exit, then returns its exit status and the resources it used.
+///
+/// The resources are only known on Unix, where the child is waited with `wait4`.
+#[cfg(not(unix))]
+pub fn wait(child: &mut Child) -> Result<(ExitStatus, Option<Usage>)> {
+    child.wait().map(|status| (status, None))
+}
+
+#[test]
+fn test() {
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1536), "1.50 KB");
+    assert_eq!(format_bytes(56_832_819), "54.20 MB");
+}